@@ -2,6 +2,9 @@ use std::fmt::{Display, Debug, Formatter};
 use std::collections::VecDeque;
 use cards_core::*;
 
+/// Camicia only cares about the 1s, 2s and 3s, so any 40-card deck will do
+pub const DECKS: [DeckKind; 13] = DeckKind::FORTY_CARDS;
+
 #[derive(Clone, Debug)]
 pub struct Game {
     pub turn: Turn,
@@ -10,6 +13,8 @@ pub struct Game {
     pub player_second: Deck,
     /// Option of 1, 2 or 3 cards to force-throw
     pub forced_move: Option<usize>,
+    pub style: CardStyle,
+    pub deck_kind: DeckKind,
 }
 
 #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
@@ -18,11 +23,29 @@ pub enum Turn {
     First,
     Second,
 }
+
+/// Camicia plays itself: all a player can do is flip their top card onto the pile
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Play {
+    Flip,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PlayError {
+    /// Someone already ran out of cards
+    GameIsOver,
+}
     
 
 impl Game {
     pub fn new() -> Game {
-        let mut first = Card::shuffled_basic_deck().0;
+        Self::new_with(&mut rand::thread_rng())
+    }
+    pub fn from_seed(seed: u64) -> Game {
+        Self::new_with(&mut seeded_rng(seed))
+    }
+    pub fn new_with<R: rand::Rng + ?Sized>(rng: &mut R) -> Game {
+        let mut first = Card::shuffled_basic_deck_with(rng).0;
         let second = first.split_off(first.len()/2);
         Game {
             pile: Deck(VecDeque::new()),
@@ -30,6 +53,8 @@ impl Game {
             player_second: Deck(second),
             turn: Turn::First,
             forced_move: None,
+            style: CardStyle::default(),
+            deck_kind: DeckKind::default(),
         }
     }
     /// Option of winner
@@ -102,6 +127,7 @@ impl Game {
 
 impl Display for Game {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let print_card = |c: &Card| self.style.render(c, self.deck_kind);
         write!(f,
                r#"Turn: {:?}
 First top card: {} ({} cards),
@@ -110,11 +136,11 @@ Pile top card: {} ({} cards),
 Forced move?: {}
 "#,
                self.turn,
-               self.player_first.top().map(|c| c.to_string()).unwrap_or("NONE".to_string()),
+               self.player_first.top().map(print_card).unwrap_or("NONE".to_string()),
                self.player_first.len(),
-               self.player_second.top().map(|c| c.to_string()).unwrap_or("NONE".to_string()),
+               self.player_second.top().map(print_card).unwrap_or("NONE".to_string()),
                self.player_second.len(),
-               self.pile.top().map(|c| c.to_string()).unwrap_or("NONE".to_string()),
+               self.pile.top().map(print_card).unwrap_or("NONE".to_string()),
                self.pile.len(),
                self.forced_move.map(|n| n.to_string()).unwrap_or("NONE".to_string()),
         )
@@ -132,3 +158,43 @@ impl std::ops::Not for Turn {
     }
 }
 
+impl Default for Game {
+    fn default() -> Self { Self::new() }
+}
+
+impl TurnBased for Game {
+    type Player = Turn;
+    type Move = Play;
+    type Error = PlayError;
+
+    fn current_player(&self) -> Turn {
+        self.turn
+    }
+    fn legal_moves(&self) -> Vec<Play> {
+        if self.is_over().is_some() { vec![] } else { vec![Play::Flip] }
+    }
+    fn apply_move(&mut self, mov: &Play) -> Result<(), PlayError> {
+        if self.is_over().is_some() { return Err(PlayError::GameIsOver) }
+        match mov {
+            Play::Flip => self.tick(),
+        }
+        Ok(())
+    }
+    fn outcome(&self) -> Option<Outcome<Turn>> {
+        self.is_over().map(Outcome::Winner)
+    }
+}
+
+
+// ============ TESTS ================
+#[test]
+fn flips_keep_every_card() {
+    let mut game = Game::from_seed(7);
+    let outcome = play_out(&mut game, 1000, |_, moves| moves.first().copied()).unwrap();
+    assert_eq!(game.player_first.len() + game.player_second.len() + game.pile.len(), 40);
+
+    let Some(Outcome::Winner(winner)) = outcome else { panic!("seed 7 finishes well within 1000 flips") };
+    assert_eq!(game.apply_move(&Play::Flip), Err(PlayError::GameIsOver));
+    assert_eq!(game.legal_moves(), vec![]);
+    assert_ne!(game.current_player(), winner);
+}
@@ -1,7 +1,17 @@
 use camicia::*;
+use cards_core::{arg_parsed, random_seed, usage_error};
 
 fn main() {
-    let mut game = Game::new();
+    // `--seed N` replays the exact same deal
+    let seed = arg_parsed("--seed").unwrap_or_else(random_seed);
+    let mut game = Game::from_seed(seed);
+    if let Some(style) = arg_parsed("--style") {
+        game.style = style;
+    }
+    if let Some(deck) = arg_parsed("--deck") {
+        if !DECKS.contains(&deck) { usage_error("camicia is played with a 40-card deck") }
+        game.deck_kind = deck;
+    }
     let mut rounds = 0;
 
     const IS_AUTOMATIC: bool = true;  // Change at compile time
@@ -15,7 +25,7 @@ fn main() {
         }
         match game.is_over() {
             Some(winner) => {
-                if !IS_AUTOMATIC { println!("{winner:?} won! WOOO. It took '{rounds}' rounds"); }
+                if !IS_AUTOMATIC { println!("{winner:?} won! WOOO. It took '{rounds}' rounds (seed was '{seed}')"); }
                 break;
             },
             None => game.tick(),
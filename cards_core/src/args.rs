@@ -0,0 +1,29 @@
+//! Command line options of the workspace's binaries, all of them `--name value`
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// Value given to `--name` on the command line, if any
+pub fn arg_value(name: &str) -> Option<String> {
+    let args: Vec<String> = std::env::args().collect();
+    let i = args.iter().position(|a| a == name)?;
+    args.get(i + 1).cloned()
+}
+
+/// Value given to `--name`, parsed. One that doesn't parse is a `usage_error`
+pub fn arg_parsed<T: FromStr>(name: &str) -> Option<T>
+where
+    T::Err: Display,
+{
+    let value = arg_value(name)?;
+    match value.parse() {
+        Ok(parsed) => Some(parsed),
+        Err(e)     => usage_error(format!("{name} '{value}': {e}")),
+    }
+}
+
+/// Say what's wrong with the command line and exit, a typo isn't worth a backtrace
+pub fn usage_error(message: impl Display) -> ! {
+    eprintln!("error: {message}");
+    std::process::exit(2)
+}
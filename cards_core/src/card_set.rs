@@ -0,0 +1,205 @@
+//! Dense `u8` encoding of cards and a `u64` bitset built on top of it.
+//!
+//! A card's index is `suit * 13 + rank`, where the rank goes Asso (0), 2..10 (1..9), Fante (10),
+//! Cavallo (11), Re (12), so every Italian and French card fits in `0..52`. The red and black
+//! jokers are 52 and 53. A `CardNum::Numeric` outside of 1..=10 (see `Card::try_new`) has no
+//! index, and so is never in a `CardSet`.
+//!
+//! The `back` isn't encoded: copies of the same card share an index, so a `CardSet` made from a
+//! multi-deck shoe (see `DeckKind::seeded_shoe`) holds each card once, however many copies of it
+//! there are.
+
+use std::fmt::{Debug, Formatter};
+use std::ops::{BitAnd, BitOr, Sub};
+
+use crate::*;
+
+/// One past the highest index
+pub const CARD_INDICES: u8 = 54;
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct CardSet(pub u64);
+
+impl Card {
+    /// `None` for a numeric outside of 1..=10
+    pub fn index(&self) -> Option<u8> {
+        let rank = match self.number {
+            CardNum::Numeric(n @ 1..=10) => n - 1,
+            CardNum::Numeric(_) => return None,
+            CardNum::Fante      => 10,
+            CardNum::Cavallo    => 11,
+            CardNum::Re         => 12,
+            CardNum::Joker      => return match self.colour() {
+                Colour::Red   => Some(52),
+                Colour::Black => Some(53),
+            },
+        };
+        Some(self.suit as u8 * 13 + rank)
+    }
+
+    /// This card's bit in a `CardSet`, none if it has no index
+    fn bit(&self) -> u64 {
+        self.index().map_or(0, |i| 1 << i)
+    }
+
+    /// Inverse of `Card::index`, with `back` 0
+    pub fn from_index(i: u8) -> Option<Card> {
+        let suit = match i / 13 {
+            0 => Suit::Denari,
+            1 => Suit::Coppe,
+            2 => Suit::Bastoni,
+            3 => Suit::Spade,
+            _ => return match i {
+                52 => Some(Card::joker(Colour::Red)),
+                53 => Some(Card::joker(Colour::Black)),
+                _  => None,
+            },
+        };
+        let number = match i % 13 {
+            n @ 0..=9 => CardNum::Numeric(n + 1),
+            10        => CardNum::Fante,
+            11        => CardNum::Cavallo,
+            _         => CardNum::Re,
+        };
+        Some(Card::new(suit, number))
+    }
+}
+
+impl CardSet {
+    pub const fn new() -> CardSet {
+        CardSet(0)
+    }
+    /// Every card of a `kind` deck, jokers aside
+    pub fn full(kind: DeckKind) -> CardSet {
+        [Suit::Denari, Suit::Coppe, Suit::Bastoni, Suit::Spade].iter()
+            .flat_map(|&suit| kind.numbers().iter().map(move |&number| Card::new(suit, number)))
+            .collect()
+    }
+
+    /// Does nothing for a card without an index, see `Card::index`
+    pub fn insert(&mut self, card: &Card) {
+        self.0 |= card.bit();
+    }
+    pub fn remove(&mut self, card: &Card) {
+        self.0 &= !card.bit();
+    }
+    pub fn contains(&self, card: &Card) -> bool {
+        self.0 & card.bit() != 0
+    }
+    /// Whether every card of `other` is in `self`
+    pub fn contains_all(&self, other: CardSet) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn union(self, other: CardSet) -> CardSet {
+        CardSet(self.0 | other.0)
+    }
+    pub fn intersection(self, other: CardSet) -> CardSet {
+        CardSet(self.0 & other.0)
+    }
+    pub fn difference(self, other: CardSet) -> CardSet {
+        CardSet(self.0 & !other.0)
+    }
+
+    /// In index order, all with `back` 0
+    pub fn iter(&self) -> impl Iterator<Item = Card> + '_ {
+        (0..CARD_INDICES).filter(|i| self.0 & (1 << i) != 0).filter_map(Card::from_index)
+    }
+}
+
+impl FromIterator<Card> for CardSet {
+    fn from_iter<I: IntoIterator<Item = Card>>(iter: I) -> CardSet {
+        let mut set = CardSet::new();
+        set.extend(iter);
+        set
+    }
+}
+impl<'a> FromIterator<&'a Card> for CardSet {
+    fn from_iter<I: IntoIterator<Item = &'a Card>>(iter: I) -> CardSet {
+        iter.into_iter().copied().collect()
+    }
+}
+impl Extend<Card> for CardSet {
+    fn extend<I: IntoIterator<Item = Card>>(&mut self, iter: I) {
+        for card in iter { self.insert(&card); }
+    }
+}
+
+impl From<&Deck> for CardSet {
+    fn from(deck: &Deck) -> CardSet {
+        deck.iter().collect()
+    }
+}
+
+impl BitOr for CardSet {
+    type Output = CardSet;
+    fn bitor(self, other: CardSet) -> CardSet { self.union(other) }
+}
+impl BitAnd for CardSet {
+    type Output = CardSet;
+    fn bitand(self, other: CardSet) -> CardSet { self.intersection(other) }
+}
+impl Sub for CardSet {
+    type Output = CardSet;
+    fn sub(self, other: CardSet) -> CardSet { self.difference(other) }
+}
+
+impl Debug for CardSet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+
+// ============ TESTS ================
+#[test]
+fn indices_round_trip() {
+    let mut seen = CardSet::new();
+    for card in DeckKind::French.seeded_shoe(1, 2, 0).iter() {
+        let i = card.index().unwrap();
+        assert!(i < CARD_INDICES);
+        assert_eq!(Card::from_index(i), Some(*card));
+        assert!(!seen.contains(card));
+        seen.insert(card);
+    }
+    assert_eq!(seen.len(), CARD_INDICES as usize);
+    assert_eq!(Card::from_index(CARD_INDICES), None);
+
+    // Numerics `Card::try_new` would refuse have no index, rather than another card's
+    for n in [0, 11, 14, 255] {
+        let card = Card::new(Suit::Coppe, CardNum::Numeric(n));
+        assert_eq!(card.index(), None);
+        seen.insert(&card);
+        assert!(!seen.contains(&card));
+    }
+    assert_eq!(seen.len(), CARD_INDICES as usize);
+}
+
+#[test]
+fn set_operations() {
+    let denari: CardSet = (1..=10).map(Card::denari).collect();
+    let sevens: CardSet = [Suit::Denari, Suit::Coppe, Suit::Bastoni, Suit::Spade].iter()
+        .map(|&s| Card::new_it(s, 7)).collect();
+
+    assert_eq!(CardSet::full(DeckKind::Napoletane).len(), 40);
+    assert_eq!(CardSet::full(DeckKind::French).len(), 52);
+    assert!(CardSet::full(DeckKind::Napoletane).contains_all(denari));
+    assert_eq!(denari.len(), 10);
+    assert_eq!((denari & sevens).iter().collect::<Vec<Card>>(), vec![Card::denari(7)]);
+    assert_eq!((denari | sevens).len(), 13);
+    assert_eq!((denari - sevens).len(), 9);
+    assert!(denari.contains_all(denari & sevens));
+    assert!(!denari.contains_all(sevens));
+
+    let mut set = sevens;
+    set.remove(&Card::denari(7));
+    assert!(!set.contains(&Card::denari(7)));
+    assert_eq!(set.len(), 3);
+}
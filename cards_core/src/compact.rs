@@ -0,0 +1,61 @@
+//! Compact string form of cards for serde: the Italian `Notation`, e.g. `7d`, `Rb`, `Cs` or
+//! `10c`, followed by `/N` for cards from the Nth copy of a shoe (`7d/1`). This form is stable,
+//! so it's safe to use in save files and over the wire.
+//!
+//! Use it with `#[serde(with = "cards_core::compact")]` on a `Card` field, or with
+//! `#[serde(with = "cards_core::compact::deck")]` on a `Deck` field. Without these, the derived
+//! (structured) form is used instead.
+
+use serde::{Deserialize, Deserializer, Serializer};
+use serde::de::Error;
+
+use crate::*;
+
+pub fn serialize<S: Serializer>(card: &Card, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&to_compact(card))
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Card, D::Error> {
+    let s = String::deserialize(deserializer)?;
+    from_compact(&s).ok_or_else(|| D::Error::custom(format!("'{s}' is not a valid card")))
+}
+
+/// A whole `Deck` as a list of compact cards, bottom first
+pub mod deck {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use serde::de::Error;
+    use serde::ser::SerializeSeq;
+
+    use crate::*;
+    use super::{from_compact, to_compact};
+
+    pub fn serialize<S: Serializer>(deck: &Deck, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(deck.len()))?;
+        for card in deck.iter() {
+            seq.serialize_element(&to_compact(card))?;
+        }
+        seq.end()
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Deck, D::Error> {
+        let cards = Vec::<String>::deserialize(deserializer)?;
+        cards.iter()
+            .map(|s| from_compact(s).ok_or_else(|| D::Error::custom(format!("'{s}' is not a valid card"))))
+            .collect::<Result<_, _>>()
+            .map(Deck)
+    }
+}
+
+fn to_compact(card: &Card) -> String {
+    match card.back {
+        0    => Notation::Italian.format(card),
+        back => format!("{}/{back}", Notation::Italian.format(card)),
+    }
+}
+
+fn from_compact(s: &str) -> Option<Card> {
+    match s.split_once('/') {
+        None               => Notation::Italian.parse(s).ok(),
+        Some((card, back)) => Some(Notation::Italian.parse(card).ok()?.with_back(back.parse().ok()?)),
+    }
+}
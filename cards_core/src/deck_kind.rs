@@ -0,0 +1,246 @@
+//! The regional decks. Every 40-card deck holds the same `Card`s (A..7, Fante, Cavallo, Re in
+//! four suits), only their names change. French-suited decks use the same suit mapping as
+//! `Notation::French`: Cuori are `Spade`, Quadri are `Denari`, Fiori are `Coppe` and Picche are
+//! `Bastoni`, and their `Cavallo` is the Donna.
+//!
+//! Games that need more than one deck, or jokers, build a shoe with `DeckKind::shuffled_shoe`.
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use rand::Rng;
+
+use crate::*;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DeckKind {
+    // Italian suits
+    #[default]
+    Napoletane,
+    Piacentine,
+    Siciliane,
+    Trevisane,
+    Bergamasche,
+    Bresciane,
+    Triestine,
+    Romagnole,
+    // Spanish suits, named like the Italian ones
+    Sarde,
+    // French suits, 40 cards
+    Piemontesi,
+    Milanesi,
+    Genovesi,
+    Toscane,
+    // The full 52-card deck
+    French,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnknownDeck(pub String);
+
+const FORTY: [CardNum; 10] = [
+    CardNum::Numeric(1), CardNum::Numeric(2), CardNum::Numeric(3), CardNum::Numeric(4),
+    CardNum::Numeric(5), CardNum::Numeric(6), CardNum::Numeric(7),
+    CardNum::Fante, CardNum::Cavallo, CardNum::Re];
+
+const FIFTY_TWO: [CardNum; 13] = [
+    CardNum::Numeric(1), CardNum::Numeric(2), CardNum::Numeric(3), CardNum::Numeric(4),
+    CardNum::Numeric(5), CardNum::Numeric(6), CardNum::Numeric(7), CardNum::Numeric(8),
+    CardNum::Numeric(9), CardNum::Numeric(10),
+    CardNum::Fante, CardNum::Cavallo, CardNum::Re];
+
+impl DeckKind {
+    pub const ALL: [DeckKind; 14] = [
+        DeckKind::Napoletane, DeckKind::Piacentine, DeckKind::Siciliane, DeckKind::Trevisane,
+        DeckKind::Bergamasche, DeckKind::Bresciane, DeckKind::Triestine, DeckKind::Romagnole,
+        DeckKind::Sarde,
+        DeckKind::Piemontesi, DeckKind::Milanesi, DeckKind::Genovesi, DeckKind::Toscane,
+        DeckKind::French,
+    ];
+
+    /// Every 40-card deck, which is what most Italian games are played with
+    pub const FORTY_CARDS: [DeckKind; 13] = [
+        DeckKind::Napoletane, DeckKind::Piacentine, DeckKind::Siciliane, DeckKind::Trevisane,
+        DeckKind::Bergamasche, DeckKind::Bresciane, DeckKind::Triestine, DeckKind::Romagnole,
+        DeckKind::Sarde,
+        DeckKind::Piemontesi, DeckKind::Milanesi, DeckKind::Genovesi, DeckKind::Toscane,
+    ];
+
+    /// Lowercase, as given to `--deck`
+    pub fn name(self) -> &'static str {
+        match self {
+            DeckKind::Napoletane  => "napoletane",
+            DeckKind::Piacentine  => "piacentine",
+            DeckKind::Siciliane   => "siciliane",
+            DeckKind::Trevisane   => "trevisane",
+            DeckKind::Bergamasche => "bergamasche",
+            DeckKind::Bresciane   => "bresciane",
+            DeckKind::Triestine   => "triestine",
+            DeckKind::Romagnole   => "romagnole",
+            DeckKind::Sarde       => "sarde",
+            DeckKind::Piemontesi  => "piemontesi",
+            DeckKind::Milanesi    => "milanesi",
+            DeckKind::Genovesi    => "genovesi",
+            DeckKind::Toscane     => "toscane",
+            DeckKind::French      => "french",
+        }
+    }
+
+    pub fn is_french_suited(self) -> bool {
+        matches!(self, DeckKind::Piemontesi | DeckKind::Milanesi | DeckKind::Genovesi
+                     | DeckKind::Toscane | DeckKind::French)
+    }
+
+    /// French-suited decks are written with J, Q, K and French suits
+    pub fn notation(self) -> Notation {
+        if self.is_french_suited() { Notation::French } else { Notation::Italian }
+    }
+
+    /// Every number in the deck, once
+    pub fn numbers(self) -> &'static [CardNum] {
+        match self {
+            DeckKind::French => &FIFTY_TWO,
+            _                => &FORTY,
+        }
+    }
+
+    pub fn number_of_cards(self) -> usize {
+        self.numbers().len() * 4
+    }
+
+    pub fn contains(self, card: &Card) -> bool {
+        self.numbers().contains(&card.number)
+    }
+
+    pub fn suit_name(self, suit: Suit) -> &'static str {
+        match (self.is_french_suited(), suit) {
+            (false, Suit::Denari)  => "Denari",
+            (false, Suit::Coppe)   => "Coppe",
+            (false, Suit::Bastoni) => "Bastoni",
+            (false, Suit::Spade)   => "Spade",
+            (true,  Suit::Denari)  => "Quadri",
+            (true,  Suit::Coppe)   => "Fiori",
+            (true,  Suit::Bastoni) => "Picche",
+            (true,  Suit::Spade)   => "Cuori",
+        }
+    }
+
+    /// Asso, Due, ..., Fante, Cavallo, Re. The Siciliane Fante is a Donna, and so is the Cavallo
+    /// of every French-suited deck
+    pub fn number_name(self, number: CardNum) -> &'static str {
+        match (self, number) {
+            (_, CardNum::Numeric(n)) => (n as usize).checked_sub(1)
+                .and_then(|i| ["Asso", "Due", "Tre", "Quattro", "Cinque", "Sei", "Sette", "Otto", "Nove", "Dieci"].get(i))
+                .copied().unwrap_or("?"),
+            (DeckKind::Siciliane, CardNum::Fante)        => "Donna",
+            (_, CardNum::Fante)                          => "Fante",
+            (d, CardNum::Cavallo) if d.is_french_suited() => "Donna",
+            (_, CardNum::Cavallo)                        => "Cavallo",
+            (_, CardNum::Re)                             => "Re",
+            (_, CardNum::Joker)                          => "Jolly",
+        }
+    }
+
+    /// `Sette di Denari`, `Donna di Cuori`, `Jolly rosso`
+    pub fn card_name(self, card: &Card) -> String {
+        match (card.number, card.colour()) {
+            (CardNum::Joker, Colour::Red)   => "Jolly rosso".to_string(),
+            (CardNum::Joker, Colour::Black) => "Jolly nero".to_string(),
+            (number, _) => format!("{} di {}", self.number_name(number), self.suit_name(card.suit)),
+        }
+    }
+
+    pub fn shuffled_deck(self) -> Deck {
+        self.shuffled_deck_with(&mut rand::thread_rng())
+    }
+    pub fn seeded_deck(self, seed: u64) -> Deck {
+        self.shuffled_deck_with(&mut seeded_rng(seed))
+    }
+    pub fn shuffled_deck_with<R: Rng + ?Sized>(self, rng: &mut R) -> Deck {
+        Card::shuffled_deck(self.numbers(), rng)
+    }
+
+    /// `decks` copies of this deck shuffled together, each copy with its own `back` (0, 1, ...),
+    /// plus `jokers` jokers per copy, alternating red and black. E.g. Scala 40 and Burraco are
+    /// played with `DeckKind::French.shuffled_shoe(2, 2)`
+    pub fn shuffled_shoe(self, decks: u8, jokers: u8) -> Deck {
+        self.shuffled_shoe_with(decks, jokers, &mut rand::thread_rng())
+    }
+    pub fn seeded_shoe(self, decks: u8, jokers: u8, seed: u64) -> Deck {
+        self.shuffled_shoe_with(decks, jokers, &mut seeded_rng(seed))
+    }
+    pub fn shuffled_shoe_with<R: Rng + ?Sized>(self, decks: u8, jokers: u8, rng: &mut R) -> Deck {
+        let mut shoe = Deck::new();
+        for back in 0..decks {
+            for card in self.shuffled_deck_with(rng).iter() {
+                shoe.push_to_top(card.with_back(back));
+            }
+            for j in 0..jokers {
+                let colour = if j % 2 == 0 { Colour::Red } else { Colour::Black };
+                shoe.push_to_top(Card::joker(colour).with_back(back));
+            }
+        }
+        shoe.shuffle_with(rng);
+        shoe
+    }
+}
+
+impl Display for DeckKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for DeckKind {
+    type Err = UnknownDeck;
+
+    fn from_str(s: &str) -> Result<DeckKind, UnknownDeck> {
+        DeckKind::ALL.into_iter()
+            .find(|d| d.name() == s)
+            .ok_or_else(|| UnknownDeck(s.to_string()))
+    }
+}
+
+impl Display for UnknownDeck {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let names: Vec<&str> = DeckKind::ALL.iter().map(|d| d.name()).collect();
+        write!(f, "unknown deck '{}', expected one of: {}", self.0, names.join(", "))
+    }
+}
+
+impl std::error::Error for UnknownDeck {}
+
+
+// ============ TESTS ================
+#[test]
+fn regional_names() {
+    let fante_di_spade = Card::new_it(Suit::Spade, 8);
+    assert_eq!(DeckKind::Napoletane.card_name(&fante_di_spade), "Fante di Spade");
+    assert_eq!(DeckKind::Siciliane.card_name(&fante_di_spade), "Donna di Spade");
+    assert_eq!(DeckKind::Piemontesi.card_name(&Card::new_it(Suit::Spade, 9)), "Donna di Cuori");
+    assert_eq!(DeckKind::French.card_name(&Card::new_fr(Suit::Denari, 10)), "Dieci di Quadri");
+    assert_eq!(DeckKind::Napoletane.number_name(CardNum::Numeric(0)), "?");
+    assert_eq!(DeckKind::Napoletane.number_name(CardNum::Numeric(11)), "?");
+
+    for deck in DeckKind::ALL {
+        assert_eq!(deck.seeded_deck(1).len(), deck.number_of_cards());
+        assert!(deck.seeded_deck(1).iter().all(|c| deck.contains(c)));
+        assert_eq!(deck.name().parse(), Ok(deck));
+    }
+    assert!(DeckKind::FORTY_CARDS.iter().all(|d| d.number_of_cards() == 40));
+    assert_eq!(DeckKind::French.number_of_cards(), 52);
+}
+
+#[test]
+fn shoes() {
+    let shoe = DeckKind::French.seeded_shoe(2, 2, 9);
+    assert_eq!(shoe.len(), 2 * 54);
+    assert_eq!(shoe.iter().filter(|c| c.is_joker()).count(), 4);
+    assert_eq!(shoe.iter().filter(|c| **c == Card::joker(Colour::Red).with_back(1)).count(), 1);
+
+    let sette_bello = Card::denari(7);
+    let copies: Vec<&Card> = shoe.iter().filter(|c| c.same_face(&sette_bello)).collect();
+    assert_eq!(copies.len(), 2);
+    assert_ne!(copies[0], copies[1]);
+    assert_eq!(shoe, DeckKind::French.seeded_shoe(2, 2, 9));
+}
@@ -0,0 +1,45 @@
+use std::fmt::{Display, Formatter};
+
+use crate::*;
+
+/// Why a card couldn't be made
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CardError {
+    /// Italian cards are numbered 1 to 10
+    OutOfRangeIt(usize),
+    /// French cards are numbered 1 to 13
+    OutOfRangeFr(usize),
+    /// `CardNum::Numeric` only goes from 1 to 10
+    InvalidNumeric(u8),
+    /// Not a `Card::index`
+    InvalidIndex(u8),
+    /// The text wasn't a card
+    Parse(ParseCardError),
+}
+
+impl Display for CardError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            CardError::OutOfRangeIt(n)   => write!(f, "italian cards go from 1 to 10, got {n}"),
+            CardError::OutOfRangeFr(n)   => write!(f, "french cards go from 1 to 13, got {n}"),
+            CardError::InvalidNumeric(n) => write!(f, "numeric cards go from 1 to 10, got {n}"),
+            CardError::InvalidIndex(i)   => write!(f, "{i} is not a card index"),
+            CardError::Parse(e)          => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for CardError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CardError::Parse(e) => Some(e),
+            _                   => None,
+        }
+    }
+}
+
+impl From<ParseCardError> for CardError {
+    fn from(e: ParseCardError) -> CardError {
+        CardError::Parse(e)
+    }
+}
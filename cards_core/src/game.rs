@@ -0,0 +1,92 @@
+//! What every game in the workspace has in common, so that drivers, bots, replays and test
+//! harnesses can be written once and work for all of them.
+
+use std::fmt::Debug;
+
+/// A game where players take turns making moves, one at a time
+pub trait TurnBased {
+    /// Whoever is making the moves: `()` for single player games
+    type Player: Copy + Eq + Debug;
+    type Move: Clone + Debug;
+    type Error: Debug;
+
+    fn current_player(&self) -> Self::Player;
+
+    /// Every move `current_player` may make right now. Empty once the game is over
+    fn legal_moves(&self) -> Vec<Self::Move>;
+
+    /// Make a move as `current_player`. Illegal moves are rejected and leave the game untouched
+    fn apply_move(&mut self, mov: &Self::Move) -> Result<(), Self::Error>;
+
+    /// `None` while the game is still going
+    fn outcome(&self) -> Option<Outcome<Self::Player>>;
+
+    fn is_over(&self) -> bool {
+        self.outcome().is_some()
+    }
+}
+
+/// A game where players don't get to see everything. Bots, network clients and replays should
+/// only ever be handed a `View`, never the game itself
+pub trait HiddenInformation: TurnBased {
+    type View;
+
+    /// What `player` may know, and nothing more
+    fn view(&self, player: Self::Player) -> Self::View;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome<P> {
+    /// Games played in partnerships say how a team is named by a player, see their `outcome`
+    Winner(P),
+    Draw,
+}
+
+/// Keep making the move `choose` picks out of the legal ones until the game is over, `choose`
+/// gives up (returns `None`) or `max_moves` have been made. Returns the outcome, if there was one
+pub fn play_out<G, F>(game: &mut G, max_moves: usize, mut choose: F) -> Result<Option<Outcome<G::Player>>, G::Error>
+where
+    G: TurnBased,
+    F: FnMut(&G, &[G::Move]) -> Option<G::Move>,
+{
+    for _ in 0..max_moves {
+        if let Some(outcome) = game.outcome() { return Ok(Some(outcome)); }
+
+        let moves = game.legal_moves();
+        let Some(mov) = choose(game, &moves) else { break };
+        game.apply_move(&mov)?;
+    }
+    Ok(game.outcome())
+}
+
+
+// ============ TESTS ================
+#[test]
+fn play_out_stops_when_over() {
+    /// Take 1 or 2 from a heap, whoever takes the last one wins
+    struct Nim { heap: u8, turn: bool }
+    impl TurnBased for Nim {
+        type Player = bool;
+        type Move = u8;
+        type Error = u8;
+        fn current_player(&self) -> bool { self.turn }
+        fn legal_moves(&self) -> Vec<u8> { (1..=self.heap.min(2)).collect() }
+        fn apply_move(&mut self, mov: &u8) -> Result<(), u8> {
+            if !self.legal_moves().contains(mov) { return Err(*mov) }
+            self.heap -= mov;
+            self.turn = !self.turn;
+            Ok(())
+        }
+        fn outcome(&self) -> Option<Outcome<bool>> {
+            (self.heap == 0).then_some(Outcome::Winner(!self.turn))
+        }
+    }
+
+    let mut nim = Nim { heap: 5, turn: false };
+    assert_eq!(play_out(&mut nim, 100, |_, moves| moves.last().copied()), Ok(Some(Outcome::Winner(false)))); // 2, 2, 1
+    assert!(nim.is_over());
+
+    let mut nim = Nim { heap: 5, turn: false };
+    assert_eq!(play_out(&mut nim, 1, |_, moves| moves.last().copied()), Ok(None));
+    assert_eq!(play_out(&mut nim, 100, |_, _| Some(3)), Err(3));
+}
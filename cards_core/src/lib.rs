@@ -1,14 +1,55 @@
 use std::fmt::{Display, Formatter};
 use std::fmt::Debug;
 use std::collections::VecDeque;
+use rand::{Rng, SeedableRng};
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+pub use rand;
+
+mod notation;
+pub use notation::*;
+
+mod style;
+pub use style::*;
+
+mod deck_kind;
+pub use deck_kind::*;
+
+mod card_set;
+pub use card_set::*;
+
+mod ranking;
+pub use ranking::*;
+
+mod error;
+pub use error::*;
+
+mod game;
+pub use game::*;
+
+mod args;
+pub use args::*;
+
+#[cfg(feature = "serde")]
+pub mod compact;
+
+/// The rng behind every seeded shuffle: the same seed always yields the same deal, on any machine.
+/// Unlike `StdRng`, ChaCha8's output is fixed across rand releases
+pub type SeededRng = rand_chacha::ChaCha8Rng;
+
+/// Ordered by suit, then number, then deck copy. For a game's own order, see `Ranking`
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Card {
-    pub suit: Suit,
-    pub number: CardNum
+    pub suit: Suit,   // A joker's suit only tells its colour, see `Card::joker`
+    pub number: CardNum,
+    /// Which copy of the deck this card comes from (its back colour), so that the duplicates of
+    /// a shoe can be told apart. Always 0 when playing with a single deck
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub back: u8,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
 pub enum Suit {
     Denari,
@@ -17,12 +58,21 @@ pub enum Suit {
     Spade, 
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CardNum {
     Numeric(u8),
     Fante,
     Cavallo,
-    Re
+    Re,
+    Joker,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Colour {
+    Red,
+    Black,
 }
 
 // [Bottom of deck .... Top of deck]
@@ -31,7 +81,8 @@ pub enum CardNum {
 // |----------+-----|
 // | Front    | Bot |
 // | Back     | Top |
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Deck(pub VecDeque<Card>);
 
 impl Deck {
@@ -62,6 +113,13 @@ impl Deck {
     pub fn is_empty(&self) -> bool {
         self.0.is_empty()
     }
+    /// Fisher-Yates my beloved
+    pub fn shuffle_with<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        for i in (1..self.len()).rev() {
+            let j = rng.gen_range(0..=i);
+            self.swap(i, j);
+        }
+    }
     pub fn move_all_cards_to(&mut self, dest: &mut Deck) {
         for card in self.0.drain(..) {
             dest.push_to_bottom(card)
@@ -70,35 +128,82 @@ impl Deck {
 }
 
 impl Card {
-    pub fn new_it(suit: Suit, n: usize) -> Card {
+    /// A card from the first (or only) deck
+    pub const fn new(suit: Suit, number: CardNum) -> Card {
+        Card { suit, number, back: 0 }
+    }
+
+    /// Like `Card::new`, but rejects numerics outside of 1..=10
+    pub fn try_new(suit: Suit, number: CardNum) -> Result<Card, CardError> {
+        match number {
+            CardNum::Numeric(n) if !(1..=10).contains(&n) => Err(CardError::InvalidNumeric(n)),
+            _ => Ok(Card::new(suit, number)),
+        }
+    }
+
+    /// Italian numbering: 8, 9 and 10 are Fante, Cavallo and Re
+    pub fn try_new_it(suit: Suit, n: usize) -> Result<Card, CardError> {
         match n {
-            1..=7 => Card { suit, number: CardNum::Numeric(n as u8) },
-            8     => Card { suit, number: CardNum::Fante },
-            9     => Card { suit, number: CardNum::Cavallo },
-            10    => Card { suit, number: CardNum::Re },
-            _     => panic!("Tried to make a card that's greater than 10"),
+            1..=7 => Ok(Card::new(suit, CardNum::Numeric(n as u8))),
+            8     => Ok(Card::new(suit, CardNum::Fante)),
+            9     => Ok(Card::new(suit, CardNum::Cavallo)),
+            10    => Ok(Card::new(suit, CardNum::Re)),
+            _     => Err(CardError::OutOfRangeIt(n)),
         }
     }
-    pub fn new_fr(suit: Suit, n: usize) -> Card {
+    /// French numbering: 11, 12 and 13 are Fante, Cavallo and Re
+    pub fn try_new_fr(suit: Suit, n: usize) -> Result<Card, CardError> {
         match n {
-            1..=10 => Card { suit, number: CardNum::Numeric(n as u8) },
-            11     => Card { suit, number: CardNum::Fante },
-            12     => Card { suit, number: CardNum::Cavallo },
-            13     => Card { suit, number: CardNum::Re },
-            _      => panic!("Tried to make a card that's greater than 10"),
+            1..=10 => Ok(Card::new(suit, CardNum::Numeric(n as u8))),
+            11     => Ok(Card::new(suit, CardNum::Fante)),
+            12     => Ok(Card::new(suit, CardNum::Cavallo)),
+            13     => Ok(Card::new(suit, CardNum::Re)),
+            _      => Err(CardError::OutOfRangeFr(n)),
         }
     }
+    pub fn try_denari(n: usize) -> Result<Card, CardError> {
+        Self::try_new_it(Suit::Denari, n)
+    }
 
+    /// Panics if `n` isn't in 1..=10, see `Card::try_new_it`
+    pub fn new_it(suit: Suit, n: usize) -> Card {
+        Self::try_new_it(suit, n).unwrap_or_else(|e| panic!("{e}"))
+    }
+    /// Panics if `n` isn't in 1..=13, see `Card::try_new_fr`
+    pub fn new_fr(suit: Suit, n: usize) -> Card {
+        Self::try_new_fr(suit, n).unwrap_or_else(|e| panic!("{e}"))
+    }
+    /// Panics if `n` isn't in 1..=10, see `Card::try_denari`
     pub fn denari(n: usize) -> Card {
-        match n { 
-            1..=7 => Card { suit: Suit::Denari, number: CardNum::Numeric(n as u8) },
-            8     => Card { suit: Suit::Denari, number: CardNum::Fante },
-            9     => Card { suit: Suit::Denari, number: CardNum::Cavallo },
-            10    => Card { suit: Suit::Denari, number: CardNum::Re },
-            _     => panic!("Tried to make a card that's greater than 10")
+        Self::try_denari(n).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Jokers are stored with the first suit of their colour: Denari for red, Coppe for black
+    pub const fn joker(colour: Colour) -> Card {
+        match colour {
+            Colour::Red   => Card::new(Suit::Denari, CardNum::Joker),
+            Colour::Black => Card::new(Suit::Coppe,  CardNum::Joker),
         }
     }
 
+    pub fn is_joker(&self) -> bool {
+        self.number == CardNum::Joker
+    }
+
+    pub fn colour(&self) -> Colour {
+        self.suit.colour()
+    }
+
+    /// The same card, but from the `back`th copy of the deck
+    pub fn with_back(self, back: u8) -> Card {
+        Card { back, ..self }
+    }
+
+    /// Same suit and number, regardless of which deck copy they come from
+    pub fn same_face(&self, other: &Card) -> bool {
+        self.suit == other.suit && self.number == other.number
+    }
+
     /// Assuming italian standard value counting
     pub fn value(&self) -> usize {
         match self.number {
@@ -106,6 +211,7 @@ impl Card {
             CardNum::Fante      => 8,
             CardNum::Cavallo    => 9,
             CardNum::Re         => 10,
+            CardNum::Joker      => 0,
         }
     }
 
@@ -115,44 +221,59 @@ impl Card {
             CardNum::Fante      => 11,
             CardNum::Cavallo    => 12,
             CardNum::Re         => 13,
+            CardNum::Joker      => 0,
         }
     }
 
     pub fn shuffled_basic_deck() -> Deck {
-        Self::shuffled_deck(
-            &[CardNum::Numeric(1), CardNum::Numeric(2), CardNum::Numeric(3),
-            CardNum::Numeric(4), CardNum::Numeric(5), CardNum::Numeric(6),
-            CardNum::Numeric(7), CardNum::Fante, CardNum::Cavallo, CardNum::Re])
+        Self::shuffled_basic_deck_with(&mut rand::thread_rng())
     }
     pub fn shuffled_french_deck() -> Deck {
-        Self::shuffled_deck(
-            &[CardNum::Numeric(1), CardNum::Numeric(2), CardNum::Numeric(3),
-            CardNum::Numeric(4), CardNum::Numeric(5), CardNum::Numeric(6),
-            CardNum::Numeric(7), CardNum::Numeric(8), CardNum::Numeric(9), 
-            CardNum::Numeric(10), CardNum::Fante, CardNum::Cavallo, CardNum::Re])
+        Self::shuffled_french_deck_with(&mut rand::thread_rng())
     }
 
-    fn shuffled_deck(numbers: &[CardNum]) -> Deck {
+    /// Same deck for the same seed, useful for replaying a deal
+    pub fn seeded_basic_deck(seed: u64) -> Deck {
+        Self::shuffled_basic_deck_with(&mut seeded_rng(seed))
+    }
+    pub fn seeded_french_deck(seed: u64) -> Deck {
+        Self::shuffled_french_deck_with(&mut seeded_rng(seed))
+    }
+
+    /// Any 40-card deck, see `DeckKind`
+    pub fn shuffled_basic_deck_with<R: Rng + ?Sized>(rng: &mut R) -> Deck {
+        DeckKind::Napoletane.shuffled_deck_with(rng)
+    }
+    pub fn shuffled_french_deck_with<R: Rng + ?Sized>(rng: &mut R) -> Deck {
+        DeckKind::French.shuffled_deck_with(rng)
+    }
+
+    fn shuffled_deck<R: Rng + ?Sized>(numbers: &[CardNum], rng: &mut R) -> Deck {
         let suits = [Suit::Denari, Suit::Coppe, Suit::Bastoni, Suit::Spade];
 
         let mut deck = VecDeque::with_capacity(numbers.len()*suits.len());
         for number in numbers {
             for suit in suits {
-                deck.push_back(Card { number: *number, suit  } )
+                deck.push_back(Card::new(suit, *number))
             }
         }
 
-        // Shuffle the deck (Fisher-Yates my beloved)
-        use rand::Rng;
-        let mut rng = rand::thread_rng();
+        let mut deck = Deck(deck);
+        deck.shuffle_with(rng);
+        deck
+    }
+}
 
-        for i in (1..deck.len()).rev() {
-            let j = rng.gen_range(0..=i);
-            deck.swap(i, j);
-        }
+/// Build the rng used by the `seeded_*` constructors, for games that need more than one deal
+/// out of a single seed
+pub fn seeded_rng(seed: u64) -> SeededRng {
+    SeededRng::seed_from_u64(seed)
+}
 
-        Deck(deck)
-    }
+/// A fresh seed, for when the caller doesn't care which deal they get but wants to be able to
+/// report it afterwards
+pub fn random_seed() -> u64 {
+    rand::thread_rng().gen()
 }
 
 impl Display for CardNum {
@@ -163,6 +284,7 @@ impl Display for CardNum {
             CardNum::Fante      => write!(f, "🧍"),
             CardNum::Cavallo    => write!(f, "🐴"),
             CardNum::Re         => write!(f, "👑"),
+            CardNum::Joker      => write!(f, "🃏"),
         }
     }
 }
@@ -178,18 +300,49 @@ impl Display for Suit {
     }
 }
 
+impl Suit {
+    /// Same colours as the French suits they're drawn as (see `Notation`)
+    pub fn colour(&self) -> Colour {
+        match self {
+            Suit::Denari | Suit::Spade   => Colour::Red,
+            Suit::Coppe  | Suit::Bastoni => Colour::Black,
+        }
+    }
+}
+
+impl Display for Colour {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            Colour::Red   => write!(f, "🟥"),
+            Colour::Black => write!(f, "⬛"),
+        }
+    }
+}
+
 impl Debug for Card {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
-        write!(f, "{}{}", self.number, self.suit)
+        write!(f, "{self}")?;
+        if self.back != 0 { write!(f, "#{}", self.back)?; }
+        Ok(())
     }
 }
 impl Display for Card {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
-        write!(f, "{}{}", self.number, self.suit)
+        if self.is_joker() { write!(f, "{}{}", self.number, self.colour()) }
+        else { write!(f, "{}{}", self.number, self.suit) }
     }
 }
 
 
+impl TryFrom<u8> for Card {
+    type Error = CardError;
+
+    /// See `Card::index`
+    fn try_from(i: u8) -> Result<Card, CardError> {
+        Card::from_index(i).ok_or(CardError::InvalidIndex(i))
+    }
+}
+
 impl std::ops::Deref for Deck {
     type Target = VecDeque<Card>;
     fn deref(&self) -> &VecDeque<Card> { &self.0 }
@@ -197,3 +350,71 @@ impl std::ops::Deref for Deck {
 impl std::ops::DerefMut for Deck {
     fn deref_mut(&mut self) -> &mut VecDeque<Card> { &mut self.0 }
 }
+
+
+// ============ TESTS ================
+#[test]
+fn same_seed_same_deck() {
+    assert_eq!(Card::seeded_basic_deck(42), Card::seeded_basic_deck(42));
+    assert_eq!(Card::seeded_french_deck(42), Card::seeded_french_deck(42));
+    assert_ne!(Card::seeded_basic_deck(42), Card::seeded_basic_deck(43));
+}
+
+#[test]
+fn shuffles_keep_every_card() {
+    let deck = Card::seeded_french_deck(7);
+    assert_eq!(deck.len(), 52);
+    for suit in [Suit::Denari, Suit::Coppe, Suit::Bastoni, Suit::Spade] {
+        for n in 1..=13 {
+            assert!(deck.contains(&Card::new_fr(suit, n)));
+        }
+    }
+}
+
+#[test]
+fn fallible_constructors() {
+    assert_eq!(Card::try_new_it(Suit::Coppe, 10), Ok(Card::new(Suit::Coppe, CardNum::Re)));
+    assert_eq!(Card::try_new_it(Suit::Coppe, 11), Err(CardError::OutOfRangeIt(11)));
+    assert_eq!(Card::try_new_it(Suit::Coppe, 0), Err(CardError::OutOfRangeIt(0)));
+    assert_eq!(Card::try_new_fr(Suit::Coppe, 13), Ok(Card::new(Suit::Coppe, CardNum::Re)));
+    assert_eq!(Card::try_new_fr(Suit::Coppe, 14), Err(CardError::OutOfRangeFr(14)));
+    assert_eq!(Card::try_denari(0), Err(CardError::OutOfRangeIt(0)));
+    assert_eq!(Card::try_new(Suit::Spade, CardNum::Numeric(11)), Err(CardError::InvalidNumeric(11)));
+    assert_eq!(Card::try_from(53), Ok(Card::joker(Colour::Black)));
+    assert_eq!(Card::try_from(54), Err(CardError::InvalidIndex(54)));
+
+    let parsed: Result<Card, CardError> = "7x".parse::<Card>().map_err(CardError::from);
+    assert_eq!(parsed, Err(CardError::Parse(ParseCardError::UnknownSuit('x'))));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_forms_round_trip() {
+    #[derive(serde::Serialize, serde::Deserialize, PartialEq, Debug)]
+    struct Saved {
+        #[serde(with = "compact")]
+        card: Card,
+        #[serde(with = "compact::deck")]
+        deck: Deck,
+    }
+
+    let deck = Card::seeded_basic_deck(3);
+    let saved = Saved { card: Card::new_it(Suit::Bastoni, 10), deck: deck.clone() };
+    let json = serde_json::to_string(&saved).unwrap();
+    assert!(json.starts_with(r#"{"card":"Rb","deck":["#));
+    assert_eq!(serde_json::from_str::<Saved>(&json).unwrap(), saved);
+
+    let structured = serde_json::to_string(&Card::new_fr(Suit::Coppe, 10)).unwrap();
+    assert_eq!(structured, r#"{"suit":"Coppe","number":{"Numeric":10},"back":0}"#);
+    assert_eq!(serde_json::from_str::<Card>(r#"{"suit":"Coppe","number":{"Numeric":10}}"#).unwrap(), Card::new_fr(Suit::Coppe, 10));
+
+    let shoe = Saved { card: Card::joker(Colour::Red).with_back(1), deck: DeckKind::French.seeded_shoe(2, 2, 0) };
+    let json = serde_json::to_string(&shoe).unwrap();
+    assert!(json.starts_with(r#"{"card":"*r/1","deck":["#));
+    assert_eq!(serde_json::from_str::<Saved>(&json).unwrap(), shoe);
+    assert_eq!(serde_json::from_str::<Deck>(&serde_json::to_string(&deck).unwrap()).unwrap(), deck);
+
+    for bad in [r#"{"card":"07d","deck":[]}"#, r#"{"card":"Rx","deck":[]}"#, r#"{"card":"d","deck":[]}"#] {
+        assert!(serde_json::from_str::<Saved>(bad).is_err());
+    }
+}
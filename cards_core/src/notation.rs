@@ -0,0 +1,162 @@
+//! ASCII notation for cards: the rank followed by the suit's initial.
+//!
+//! | Notation | Ranks                        | Suits                                                  |
+//! |----------+------------------------------+--------------------------------------------------------|
+//! | Italian  | `A`, `2`..`10`, `F`, `C`, `R` | `d` denari, `c` coppe, `b` bastoni, `s` spade          |
+//! | French   | `A`, `2`..`10`, `J`, `Q`, `K` | `h` hearts, `d` diamonds, `c` clubs, `s` spades        |
+//!
+//! Jokers are `*r` and `*b` (red and black) in both notations. Which deck copy a card comes from
+//! (its `back`) isn't part of the notation.
+//!
+//! French suits map onto `Suit` the same way solitario draws them: hearts are `Spade`, diamonds
+//! are `Denari`, clubs are `Coppe` and spades are `Bastoni`. So `Cs` is the Cavallo di Spade
+//! while `Qs` is the Queen of spades (`Cavallo` of `Bastoni`).
+//!
+//! `str::parse::<Card>()` reads the Italian notation. Both notations round-trip exactly:
+//! `n.parse(&n.format(c)) == Ok(c)` and, for valid input, `n.format(&n.parse(s)?) == s`.
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use crate::*;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Notation {
+    #[default]
+    Italian,
+    French,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseCardError {
+    /// Nothing to parse
+    Empty,
+    /// The last character isn't a suit of this notation
+    UnknownSuit(char),
+    /// Whatever comes before the suit isn't a rank of this notation
+    UnknownRank(String),
+}
+
+impl Notation {
+    pub fn format(self, card: &Card) -> String {
+        match (card.number, card.colour()) {
+            (CardNum::Joker, Colour::Red)   => "*r".to_string(),
+            (CardNum::Joker, Colour::Black) => "*b".to_string(),
+            (number, _) => format!("{}{}", self.rank(number), self.suit(card.suit)),
+        }
+    }
+
+    pub fn parse(self, s: &str) -> Result<Card, ParseCardError> {
+        match s {
+            "*r" => return Ok(Card::joker(Colour::Red)),
+            "*b" => return Ok(Card::joker(Colour::Black)),
+            _    => {},
+        }
+        let suit_char = s.chars().last().ok_or(ParseCardError::Empty)?;
+        let suit = self.parse_suit(suit_char).ok_or(ParseCardError::UnknownSuit(suit_char))?;
+        let rank = &s[..s.len() - suit_char.len_utf8()];
+        let number = self.parse_rank(rank).ok_or_else(|| ParseCardError::UnknownRank(rank.to_string()))?;
+
+        Ok(Card::new(suit, number))
+    }
+
+    fn rank(self, number: CardNum) -> String {
+        match (self, number) {
+            (_, CardNum::Joker)                   => "*".to_string(),
+            (_, CardNum::Numeric(1))              => "A".to_string(),
+            (_, CardNum::Numeric(n))              => n.to_string(),
+            (Notation::Italian, CardNum::Fante)   => "F".to_string(),
+            (Notation::Italian, CardNum::Cavallo) => "C".to_string(),
+            (Notation::Italian, CardNum::Re)      => "R".to_string(),
+            (Notation::French,  CardNum::Fante)   => "J".to_string(),
+            (Notation::French,  CardNum::Cavallo) => "Q".to_string(),
+            (Notation::French,  CardNum::Re)      => "K".to_string(),
+        }
+    }
+
+    fn suit(self, suit: Suit) -> char {
+        match (self, suit) {
+            (_, Suit::Denari)                  => 'd',
+            (_, Suit::Coppe)                   => 'c',
+            (Notation::Italian, Suit::Bastoni) => 'b',
+            (Notation::Italian, Suit::Spade)   => 's',
+            (Notation::French,  Suit::Bastoni) => 's',
+            (Notation::French,  Suit::Spade)   => 'h',
+        }
+    }
+
+    fn parse_rank(self, s: &str) -> Option<CardNum> {
+        match (self, s) {
+            (_, "A")                  => Some(CardNum::Numeric(1)),
+            (Notation::Italian, "F")  => Some(CardNum::Fante),
+            (Notation::Italian, "C")  => Some(CardNum::Cavallo),
+            (Notation::Italian, "R")  => Some(CardNum::Re),
+            (Notation::French,  "J")  => Some(CardNum::Fante),
+            (Notation::French,  "Q")  => Some(CardNum::Cavallo),
+            (Notation::French,  "K")  => Some(CardNum::Re),
+            (_, n) => (2..=10).find(|i: &u8| i.to_string() == n).map(CardNum::Numeric), // No `07`s
+        }
+    }
+
+    fn parse_suit(self, c: char) -> Option<Suit> {
+        [Suit::Denari, Suit::Coppe, Suit::Bastoni, Suit::Spade].into_iter().find(|&s| self.suit(s) == c)
+    }
+}
+
+impl Card {
+    /// This card written in `notation`, e.g. `7d` or `Qh`
+    pub fn notation(&self, notation: Notation) -> String {
+        notation.format(self)
+    }
+}
+
+impl FromStr for Card {
+    type Err = ParseCardError;
+
+    /// Italian notation, see `Notation`
+    fn from_str(s: &str) -> Result<Card, ParseCardError> {
+        Notation::Italian.parse(s)
+    }
+}
+
+impl Display for ParseCardError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            ParseCardError::Empty          => write!(f, "no card was given"),
+            ParseCardError::UnknownSuit(c) => write!(f, "'{c}' is not a suit"),
+            ParseCardError::UnknownRank(r) => write!(f, "'{r}' is not a rank"),
+        }
+    }
+}
+
+impl std::error::Error for ParseCardError {}
+
+
+// ============ TESTS ================
+#[test]
+fn notation_round_trips() {
+    for notation in [Notation::Italian, Notation::French] {
+        for card in Card::seeded_french_deck(0).iter() {
+            let s = card.notation(notation);
+            assert_eq!(notation.parse(&s), Ok(*card));
+            assert_eq!(notation.format(&notation.parse(&s).unwrap()), s);
+        }
+    }
+}
+
+#[test]
+fn notation_examples() {
+    assert_eq!("7d".parse(), Ok(Card::new_it(Suit::Denari, 7)));
+    assert_eq!("Cs".parse(), Ok(Card::new_it(Suit::Spade, 9)));
+    assert_eq!("Rb".parse(), Ok(Card::new_it(Suit::Bastoni, 10)));
+    assert_eq!(Notation::French.parse("10h"), Ok(Card::new_fr(Suit::Spade, 10)));
+    assert_eq!(Notation::French.parse("Qs"), Ok(Card::new_fr(Suit::Bastoni, 12)));
+    assert_eq!("*b".parse(), Ok(Card::joker(Colour::Black)));
+    assert_eq!(Card::joker(Colour::Red).notation(Notation::French), "*r");
+
+    assert_eq!("".parse::<Card>(), Err(ParseCardError::Empty));
+    assert_eq!("7h".parse::<Card>(), Err(ParseCardError::UnknownSuit('h')));
+    assert_eq!("07d".parse::<Card>(), Err(ParseCardError::UnknownRank("07".to_string())));
+    assert_eq!("Kd".parse::<Card>(), Err(ParseCardError::UnknownRank("K".to_string())));
+    assert_eq!(Notation::French.parse("Rb"), Err(ParseCardError::UnknownSuit('b')));
+}
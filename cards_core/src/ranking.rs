@@ -0,0 +1,136 @@
+//! Game-specific orders of the cards. `Card`'s own `Ord` is only there for sorting and sets (suit,
+//! then number, then deck copy); games compare cards through a `Ranking` instead.
+
+use std::cmp::{Ordering, Reverse};
+
+use crate::*;
+
+pub trait Ranking {
+    /// How strong `card` is, higher beats lower. Equal strengths tie
+    fn rank(&self, card: &Card) -> u8;
+
+    fn cmp(&self, a: &Card, b: &Card) -> Ordering {
+        self.rank(a).cmp(&self.rank(b))
+    }
+
+    /// Grouped by suit, weakest first within each suit
+    fn sort_hand(&self, cards: &mut [Card]) {
+        cards.sort_by_key(|c| (c.suit, self.rank(c), *c));
+    }
+
+    /// Index of the card that takes the trick: the strongest `trump`, or failing that the
+    /// strongest card of the suit that was led. On a tie (two copies of a card) the first one wins
+    fn trick_winner(&self, trick: &[Card], trump: Option<Suit>) -> Option<usize> {
+        let lead = trick.first()?.suit;
+        trick.iter().enumerate()
+            .max_by_key(|(i, c)| (Some(c.suit) == trump, c.suit == lead, self.rank(c), Reverse(*i)))
+            .map(|(i, _)| i)
+    }
+}
+
+/// A, 3, R, C, F, 7, 6, 5, 4, 2
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Briscola;
+
+/// 3, 2, A, R, C, F, 7, 6, 5, 4
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Tressette;
+
+/// The primiera points: 7 is 21, 6 is 18, A is 16, 5 is 15, 4 is 14, 3 is 13, 2 is 12 and the
+/// faces are 10
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Primiera;
+
+/// 2 .. 10, J, Q, K, A, jokers
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FrenchAceHigh;
+
+/// A, 2 .. 10, J, Q, K, jokers
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FrenchAceLow;
+
+/// 1-based position of `card` in `order` (weakest first), 0 if it's not there
+fn position_in(order: &[CardNum], card: &Card) -> u8 {
+    order.iter().position(|n| *n == card.number).map(|p| p as u8 + 1).unwrap_or(0)
+}
+
+impl Ranking for Briscola {
+    fn rank(&self, card: &Card) -> u8 {
+        use CardNum as N;
+        position_in(&[N::Numeric(2), N::Numeric(4), N::Numeric(5), N::Numeric(6), N::Numeric(7),
+                      N::Fante, N::Cavallo, N::Re, N::Numeric(3), N::Numeric(1)], card)
+    }
+}
+
+impl Ranking for Tressette {
+    fn rank(&self, card: &Card) -> u8 {
+        use CardNum as N;
+        position_in(&[N::Numeric(4), N::Numeric(5), N::Numeric(6), N::Numeric(7),
+                      N::Fante, N::Cavallo, N::Re, N::Numeric(1), N::Numeric(2), N::Numeric(3)], card)
+    }
+}
+
+impl Ranking for Primiera {
+    fn rank(&self, card: &Card) -> u8 {
+        match card.number {
+            CardNum::Numeric(7) => 21,
+            CardNum::Numeric(6) => 18,
+            CardNum::Numeric(1) => 16,
+            CardNum::Numeric(n @ 2..=5) => 10 + n,
+            CardNum::Fante | CardNum::Cavallo | CardNum::Re => 10,
+            CardNum::Numeric(_) | CardNum::Joker => 0,
+        }
+    }
+}
+
+impl Ranking for FrenchAceHigh {
+    fn rank(&self, card: &Card) -> u8 {
+        match card.number {
+            CardNum::Numeric(1) => 14,
+            CardNum::Joker      => 15,
+            _                   => card.value_fr() as u8,
+        }
+    }
+}
+
+impl Ranking for FrenchAceLow {
+    fn rank(&self, card: &Card) -> u8 {
+        match card.number {
+            CardNum::Joker => 14,
+            _              => card.value_fr() as u8,
+        }
+    }
+}
+
+
+// ============ TESTS ================
+#[test]
+fn rankings_order() {
+    let spade = |n| Card::new_it(Suit::Spade, n);
+    let order = |ranking: &dyn Ranking| {
+        let mut hand: Vec<Card> = (1..=10).map(spade).collect();
+        hand.sort_by(|a, b| ranking.cmp(b, a)); // Strongest first
+        hand.iter().map(|c| c.notation(Notation::Italian)).collect::<Vec<String>>().join(" ")
+    };
+
+    assert_eq!(order(&Briscola),  "As 3s Rs Cs Fs 7s 6s 5s 4s 2s");
+    assert_eq!(order(&Tressette), "3s 2s As Rs Cs Fs 7s 6s 5s 4s");
+    assert_eq!(Primiera.rank(&spade(7)), 21);
+    assert_eq!(Primiera.rank(&spade(1)), 16);
+    assert_eq!(Primiera.rank(&spade(9)), 10);
+    assert_eq!(FrenchAceHigh.cmp(&Card::new_fr(Suit::Coppe, 1), &Card::new_fr(Suit::Coppe, 13)), Ordering::Greater);
+    assert_eq!(FrenchAceLow.cmp(&Card::new_fr(Suit::Coppe, 1), &Card::new_fr(Suit::Coppe, 13)), Ordering::Less);
+}
+
+#[test]
+fn trick_winners() {
+    let trick = ["3c", "Ac", "2d", "Rc"].map(|s| s.parse::<Card>().unwrap());
+    assert_eq!(Briscola.trick_winner(&trick, None), Some(1));             // Asso beats the 3
+    assert_eq!(Briscola.trick_winner(&trick, Some(Suit::Denari)), Some(2)); // Any trump wins
+    assert_eq!(Tressette.trick_winner(&trick, None), Some(0));            // 3 is the highest
+    assert_eq!(Tressette.trick_winner(&[], None), None);
+
+    let mut hand = ["Rd", "3c", "Ad", "2c"].map(|s| s.parse::<Card>().unwrap());
+    Briscola.sort_hand(&mut hand);
+    assert_eq!(hand.map(|c| c.notation(Notation::Italian)), ["Rd", "Ad", "2c", "3c"]);
+}
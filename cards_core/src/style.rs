@@ -0,0 +1,158 @@
+//! Ways of drawing a card on the terminal. Every binary takes `--style <name>`, see `CardStyle::NAMES`.
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use crate::*;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CardStyle {
+    /// `7💲`, `🐴⚔️`: what `Display` for `Card` prints
+    #[default]
+    Emoji,
+    /// `7d`, `Cs`: the deck's `Notation`. Also turns off ANSI colours
+    Ascii,
+    /// The Unicode playing card glyphs (U+1F0A1 ...)
+    Glyph,
+    /// `Sette di Denari`, `Donna di Cuori`: see `DeckKind::card_name`
+    ItalianName,
+    /// `♦7🟥`, `♣K⬛`: suit pip, rank and colour
+    FrenchPip,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnknownStyle(pub String);
+
+impl CardStyle {
+    pub const NAMES: [(&'static str, CardStyle); 5] = [
+        ("emoji",   CardStyle::Emoji),
+        ("ascii",   CardStyle::Ascii),
+        ("glyph",   CardStyle::Glyph),
+        ("italian", CardStyle::ItalianName),
+        ("french",  CardStyle::FrenchPip),
+    ];
+
+    /// `deck` is the deck the card comes from, which changes how some styles name its faces
+    /// (the Cavallo of a French deck is a Queen)
+    pub fn render(self, card: &Card, deck: DeckKind) -> String {
+        match self {
+            CardStyle::Emoji       => card.to_string(),
+            CardStyle::Ascii       => deck.notation().format(card),
+            CardStyle::Glyph       => glyph(card, deck.notation()).map_or_else(|| deck.notation().format(card), String::from),
+            CardStyle::ItalianName => deck.card_name(card),
+            CardStyle::FrenchPip   => french_pip(card),
+        }
+    }
+
+    /// Stands in for a face-down card, or one that isn't known
+    pub fn back(self) -> &'static str {
+        match self {
+            CardStyle::Emoji | CardStyle::Glyph => "🂠",
+            CardStyle::Ascii                    => "??",
+            CardStyle::ItalianName              => "Coperta",
+            CardStyle::FrenchPip                => "---", // Same width as the cards
+        }
+    }
+
+    /// Whether it's ok to print ANSI escapes (colours, bold) alongside the cards
+    pub fn colours(self) -> bool {
+        self != CardStyle::Ascii
+    }
+
+    /// Wrap `s` in the ANSI escape `code` (e.g. `1` for bold, `38;5;93` for purple), if this style allows it
+    pub fn ansi(self, code: &str, s: &str) -> String {
+        if self.colours() { format!("\x1B[{code}m{s}\x1B[0m") }
+        else { s.to_string() }
+    }
+}
+
+/// `None` for numbers no deck has, which have no glyph
+fn glyph(card: &Card, notation: Notation) -> Option<char> {
+    match (card.number, card.colour()) {
+        (CardNum::Joker, Colour::Red)   => return Some('🂿'),
+        (CardNum::Joker, Colour::Black) => return Some('🃏'),
+        _ => {},
+    }
+    let base = match card.suit {
+        Suit::Bastoni => 0x1F0A0, // Spades
+        Suit::Spade   => 0x1F0B0, // Hearts
+        Suit::Denari  => 0x1F0C0, // Diamonds
+        Suit::Coppe   => 0x1F0D0, // Clubs
+    };
+    let offset = match (card.number, notation) {
+        (CardNum::Numeric(n @ 1..=10), _)      => n as u32,
+        (CardNum::Numeric(_), _)                => return None,
+        (CardNum::Fante, _)                     => 0xB,
+        (CardNum::Cavallo, Notation::Italian)   => 0xC, // Knight
+        (CardNum::Cavallo, Notation::French)    => 0xD, // Queen
+        (CardNum::Re, _)                        => 0xE,
+        (CardNum::Joker, _)                     => unreachable!("Jokers were handled above"),
+    };
+    Some(char::from_u32(base + offset).expect("Every offset is inside the playing cards block"))
+}
+
+fn french_pip(card: &Card) -> String {
+    let (s, col) = match card.suit {
+        Suit::Spade   => ("♥", "🟥"),
+        Suit::Denari  => ("♦", "🟥"),
+        Suit::Coppe   => ("♣", "⬛"),
+        Suit::Bastoni => ("♠", "⬛"),
+    };
+
+    let num = match card.number {
+        CardNum::Numeric(1)  => "A".to_string(),
+        CardNum::Numeric(10) => "X".to_string(), // '10' is two characters, messes up the alignment
+        CardNum::Numeric(n)  =>  n.to_string(),
+        CardNum::Fante       => "J".to_string(),
+        CardNum::Cavallo     => "Q".to_string(),
+        CardNum::Re          => "K".to_string(),
+        CardNum::Joker       => return format!("**{}", card.colour()), // Same width as the rest
+    };
+
+    format!("{}{}{}", s, num, col)
+}
+
+impl FromStr for CardStyle {
+    type Err = UnknownStyle;
+
+    fn from_str(s: &str) -> Result<CardStyle, UnknownStyle> {
+        CardStyle::NAMES.iter()
+            .find(|(name, _)| *name == s)
+            .map(|(_, style)| *style)
+            .ok_or_else(|| UnknownStyle(s.to_string()))
+    }
+}
+
+impl Display for UnknownStyle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let names: Vec<&str> = CardStyle::NAMES.iter().map(|(name, _)| *name).collect();
+        write!(f, "unknown card style '{}', expected one of: {}", self.0, names.join(", "))
+    }
+}
+
+impl std::error::Error for UnknownStyle {}
+
+
+// ============ TESTS ================
+#[test]
+fn styles_render() {
+    let sette_bello = Card::new_it(Suit::Denari, 7);
+    let cavallo = Card::new_it(Suit::Spade, 9);
+
+    assert_eq!(CardStyle::Ascii.render(&sette_bello, DeckKind::Napoletane), "7d");
+    assert_eq!(CardStyle::Ascii.render(&cavallo, DeckKind::Piemontesi), "Qh");
+    assert_eq!(CardStyle::ItalianName.render(&sette_bello, DeckKind::Napoletane), "Sette di Denari");
+    assert_eq!(CardStyle::ItalianName.render(&cavallo, DeckKind::French), "Donna di Cuori");
+    assert_eq!(CardStyle::Glyph.render(&Card::new_fr(Suit::Bastoni, 1), DeckKind::French), "🂡");
+    assert_eq!(CardStyle::Glyph.render(&cavallo, DeckKind::Napoletane), "🂼");
+    assert_eq!(CardStyle::Glyph.render(&cavallo, DeckKind::French), "🂽");
+    assert_eq!(CardStyle::FrenchPip.render(&Card::new_fr(Suit::Coppe, 10), DeckKind::French), "♣X⬛");
+    assert_eq!(CardStyle::FrenchPip.render(&Card::joker(Colour::Red), DeckKind::French), "**🟥");
+    assert_eq!(CardStyle::Glyph.render(&Card::joker(Colour::Black), DeckKind::French), "🃏");
+    assert_eq!(CardStyle::Glyph.render(&Card::new(Suit::Denari, CardNum::Numeric(12)), DeckKind::Napoletane), "12d");
+    assert_eq!(CardStyle::ItalianName.render(&Card::joker(Colour::Black), DeckKind::French), "Jolly nero");
+
+    assert_eq!("glyph".parse(), Ok(CardStyle::Glyph));
+    assert!("sparkly".parse::<CardStyle>().is_err());
+    assert_eq!(CardStyle::Ascii.ansi("1", "bold"), "bold");
+}
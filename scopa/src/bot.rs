@@ -0,0 +1,185 @@
+//! Computer players. Like anybody else at the table they only get to see their `PlayerView`,
+//! never the match itself.
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use cards_core::*;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::*;
+
+/// How well a `Bot` plays
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Strength {
+    /// Any legal move
+    Random,
+    /// Whatever is worth the most right now: scope, the settebello, denari, sevens...
+    Greedy,
+    /// Plays every move out to the end of the match against `samples` guesses of the cards it
+    /// can't see (determinized Monte-Carlo), keeps the one that did best on average
+    MonteCarlo { samples: usize },
+}
+
+#[derive(Clone, Debug)]
+pub struct Bot {
+    pub strength: Strength,
+    rng: SeededRng,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnknownStrength(pub String);
+
+impl Strength {
+    pub const NAMES: [(&'static str, Strength); 3] = [
+        ("random", Strength::Random),
+        ("greedy", Strength::Greedy),
+        ("mc",     Strength::MonteCarlo { samples: 50 }),
+    ];
+}
+
+impl Bot {
+    /// Same seed, same choices given the same views
+    pub fn new(strength: Strength, seed: u64) -> Bot {
+        Bot { strength, rng: seeded_rng(seed) }
+    }
+
+    /// One of `moves` (see `Match::legal_moves`), `None` if there's nothing to choose from
+    pub fn choose(&mut self, view: &PlayerView, moves: &[Play]) -> Option<Play> {
+        match self.strength {
+            Strength::Random                 => moves.choose(&mut self.rng).cloned(),
+            Strength::Greedy                 => greedy(&view.table, &view.rules, moves).cloned(),
+            Strength::MonteCarlo { samples } => self.monte_carlo(view, moves, samples),
+        }
+    }
+
+    fn monte_carlo(&mut self, view: &PlayerView, moves: &[Play], samples: usize) -> Option<Play> {
+        if moves.len() < 2 { return moves.first().cloned(); }
+        let seat = view.seat.unwrap_or(view.turn);
+
+        let mut totals = vec![0; moves.len()];
+        for _ in 0..samples {
+            let sample = view.sample(&mut self.rng);
+            for (total, mov) in totals.iter_mut().zip(moves) {
+                *total += play_out_match(sample.clone(), mov, seat, &mut self.rng);
+            }
+        }
+        // The first of the best, like `greedy`
+        let best = totals.iter().enumerate().rev().max_by_key(|&(_, t)| t).map(|(i, _)| i)?;
+        moves.get(best).cloned()
+    }
+}
+
+/// The move in `moves` that's worth the most right now, the first one on a tie
+fn greedy<'a>(table: &[Card], rules: &ScopaRules, moves: &'a [Play]) -> Option<&'a Play> {
+    moves.iter().rev().max_by_key(|play| worth(table, rules, play))
+}
+
+/// Rough worth of `play` to whoever makes it: what's taken is good, what's left for the next
+/// player to take is bad
+fn worth(table: &[Card], rules: &ScopaRules, play: &Play) -> i32 {
+    let card_worth = |c: &Card| {
+        1 + 2 * (c.suit == Suit::Denari) as i32
+          + 10 * (*c == Card::denari(7)) as i32
+          + (Primiera.rank(c) as i32 - 10) / 4 // Sevens and sixes, for the primiera
+    };
+    match play {
+        Play::Take(card, taken) => {
+            let is_ace = card.number == CardNum::Numeric(1);
+            let sweep_is_scopa = !is_ace || !rules.asso_piglia_tutto || rules.ace_sweep_is_scopa;
+            let scopa = taken.len() == table.len() && sweep_is_scopa;
+            taken.iter().chain([card]).map(card_worth).sum::<i32>() + if scopa { 20 } else { 0 }
+        },
+        Play::Place(card) => {
+            // A table of 10 or less can be swept by the next player
+            let table_total: usize = table.iter().map(|c| c.value()).sum::<usize>() + card.value();
+            -card_worth(card) - if table_total <= 10 { 10 } else { 0 }
+        },
+    }
+}
+
+/// Make `mov` in `m`, then play the rest of the match out (greedily, with a bit of randomness)
+/// and return how far ahead of the best other team `seat`'s team finished it
+fn play_out_match<R: Rng + ?Sized>(mut m: Match, mov: &Play, seat: Seat, rng: &mut R) -> i64 {
+    let mut last_taker = None;
+    let mut next = mov.clone();
+    loop {
+        let made = m.play(&next).expect("Only legal moves are played out");
+        if made.cards_taken.is_some() { last_taker = Some(made.turn) }
+        if m.is_over() { break }
+        m.next_turn();
+
+        let moves = m.legal_moves();
+        let table: Vec<Card> = m.table.iter().copied().collect();
+        next = if rng.gen_bool(0.25) { moves.choose(rng) } else { greedy(&table, &m.rules, &moves) }
+            .expect("A match that isn't over has moves left").clone();
+    }
+    if let Some(taker) = last_taker {
+        while let Some(c) = m.table.take_from_top() { m.players[taker].pile.push_to_top(c) }
+    }
+
+    let tally = m.tally_final_points();
+    let ours = m.teams[seat];
+    let theirs = (0..m.rules.layout.number_of_teams()).filter(|&t| t != ours)
+        .map(|t| tally.points(t))
+        .max().unwrap_or(0);
+    tally.points(ours) as i64 - theirs as i64
+}
+
+impl FromStr for Strength {
+    type Err = UnknownStrength;
+
+    fn from_str(s: &str) -> Result<Strength, UnknownStrength> {
+        Strength::NAMES.iter()
+            .find(|(name, _)| *name == s)
+            .map(|(_, strength)| *strength)
+            .ok_or_else(|| UnknownStrength(s.to_string()))
+    }
+}
+
+impl Display for Strength {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            Strength::Random                 => write!(f, "random"),
+            Strength::Greedy                 => write!(f, "greedy"),
+            Strength::MonteCarlo { samples } => write!(f, "monte-carlo, {samples} samples"),
+        }
+    }
+}
+
+impl Display for UnknownStrength {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let names: Vec<&str> = Strength::NAMES.iter().map(|(name, _)| *name).collect();
+        write!(f, "unknown bot '{}', expected one of: {}", self.0, names.join(", "))
+    }
+}
+
+
+// ============ TESTS ================
+#[test]
+fn bots_make_legal_moves() {
+    // Greedy goes for the scopa and the settebello
+    let m = dealt_match(ScopaRules::default(), &["7d", "5c", "Rs"], &["3c", "4b"]);
+    let mut greedy = Bot::new(Strength::Greedy, 0);
+    assert_eq!(greedy.choose(&m.view(0), &m.legal_moves()), Some(Play::Take(card("7d"), vec![card("3c"), card("4b")])));
+
+    // The Monte-Carlo bot plays from a view, whatever the other hands really are
+    let mut mc = Bot::new(Strength::MonteCarlo { samples: 5 }, 0);
+    let mut game = Game::from_seed(2);
+    for _ in 0..20 {
+        let player = game.color_playing();
+        let play = mc.choose(&game.view(player), &game.legal_moves()).unwrap();
+        game.apply_move(&play).unwrap();
+    }
+
+    // Random against greedy, to the end
+    let mut bots = [Bot::new(Strength::Random, 1), Bot::new(Strength::Greedy, 1)];
+    let mut game = Game::from_seed(1);
+    let outcome = play_out(&mut game, 10_000, |game, moves| {
+        let player = game.color_playing();
+        let i = game.players.iter().position(|&p| p == player).unwrap();
+        bots[i].choose(&game.view(player), moves)
+    }).unwrap();
+    assert!(outcome.is_some());
+}
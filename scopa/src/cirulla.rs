@@ -0,0 +1,188 @@
+//! What cirulla adds on top of scopa, see `Mode::Cirulla`. The captures by 15 are a `Capture`
+//! rule of their own, the rest is here.
+
+use cards_core::*;
+
+use crate::*;
+
+/// Hands worth points as soon as they're dealt. Declaring shows the hand to everyone
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Declaration {
+    /// The three cards add up to less than 10
+    Low,
+    /// Three cards of the same number
+    Tris,
+}
+
+/// Things that happen in a match other than moves, in the order they happened
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event {
+    /// `seat` was dealt `hand` and declared it
+    Declared { seat: Seat, declaration: Declaration, hand: Vec<Card> },
+    /// The table was dealt adding up to 15 (one scopa) or 30 (two), the dealer at `seat` took it
+    TableTaken { seat: Seat, cards: Vec<Card>, scope: usize },
+}
+
+impl Declaration {
+    pub fn points(self) -> usize {
+        match self {
+            Declaration::Low  => 3,
+            Declaration::Tris => 10,
+        }
+    }
+
+    /// What `hand` can declare, the best if it could declare both. The matta stands in for
+    /// whatever card suits it best
+    pub fn of(hand: &[Card]) -> Option<Declaration> {
+        if hand.len() != 3 { return None; }
+        let (mattas, others): (Vec<Card>, Vec<Card>) = hand.iter().partition(|&&c| c == matta());
+
+        let same_number = others.windows(2).all(|w| w[0].number == w[1].number);
+        let total: usize = others.iter().map(|c| c.value()).sum::<usize>() + mattas.len(); // As aces
+
+        if same_number { Some(Declaration::Tris) }
+        else if total < 10 { Some(Declaration::Low) }
+        else { None }
+    }
+}
+
+/// The 7 of coppe, a wildcard in declarations. When played it's a plain 7
+pub fn matta() -> Card {
+    Card::new_it(Suit::Coppe, 7)
+}
+
+/// The fante, cavallo and re of denari together are worth 5 points
+pub fn has_grande(pile: &Deck) -> bool {
+    let pile = CardSet::from(pile);
+    [8, 9, 10].iter().all(|&n| pile.contains(&Card::denari(n)))
+}
+
+/// The asso, 2 and 3 of denari are worth 3 points, one more for each of the 4, 5 and 6 that
+/// follows them without a gap
+pub fn piccola(pile: &Deck) -> Option<usize> {
+    let pile = CardSet::from(pile);
+    let run = (1..=6).take_while(|&n| pile.contains(&Card::denari(n))).count();
+    (run >= 3).then_some(run)
+}
+
+impl Event {
+    /// Who it happened to
+    pub fn seat(&self) -> Seat {
+        match self {
+            Event::Declared { seat, .. } | Event::TableTaken { seat, .. } => *seat,
+        }
+    }
+
+    /// Says who it was by seat
+    pub fn render(&self, style: CardStyle, deck: DeckKind) -> String {
+        self.describe(&seat_name(self.seat()), style, deck)
+    }
+    pub(crate) fn describe(&self, who: &str, style: CardStyle, deck: DeckKind) -> String {
+        let cards = |cards: &[Card]| cards.iter().map(|c| style.render(c, deck)).collect::<Vec<String>>().join(" ");
+        match self {
+            Event::Declared { declaration, hand, .. } => {
+                let name = match declaration { Declaration::Low => "less than 10", Declaration::Tris => "three of a kind" };
+                format!("{who} declared {name} with {} ({} points)", cards(hand), declaration.points())
+            },
+            Event::TableTaken { cards: taken, scope, .. } =>
+                format!("{who} dealt {} and took it: {scope} scop{}", cards(taken), if *scope == 1 { "a" } else { "e" }),
+        }
+    }
+}
+
+impl Match {
+    /// Declare every hand that can be. Only in cirulla, right after each deal
+    pub(crate) fn declare_hands(&mut self) {
+        if self.rules.mode != Mode::Cirulla { return; }
+        for (seat, player) in self.players.iter().enumerate() {
+            if let Some(declaration) = Declaration::of(&player.curr_hand) {
+                self.events.push(Event::Declared { seat, declaration, hand: player.curr_hand.clone() });
+            }
+        }
+    }
+
+    /// A table dealt adding up to 15 or 30 goes to the dealer, the last seat. Only in cirulla
+    pub(crate) fn dealer_takes_fifteens(&mut self) {
+        let total: usize = self.table.iter().map(|c| c.value()).sum();
+        if self.rules.mode != Mode::Cirulla || !matches!(total, 15 | 30) { return; }
+
+        let seat = self.players.len() - 1;
+        let cards: Vec<Card> = self.table.iter().copied().collect();
+        let dealer = &mut self.players[seat];
+        while let Some(c) = self.table.take_from_top() { dealer.pile.push_to_top(c) }
+        dealer.scope += total / 15;
+        self.events.push(Event::TableTaken { seat, cards, scope: total / 15 });
+    }
+
+    /// Points `seat` has declared so far this match
+    pub fn declared(&self, seat: Seat) -> usize {
+        self.events.iter()
+            .filter_map(|e| match e {
+                Event::Declared { seat: s, declaration, .. } if *s == seat => Some(declaration.points()),
+                _ => None,
+            })
+            .sum()
+    }
+}
+
+
+// ============ TESTS ================
+#[test]
+fn cirulla_declarations_and_bonuses() {
+    assert_eq!(Declaration::of(&cards(&["Ad", "3c", "5s"])), Some(Declaration::Low));
+    assert_eq!(Declaration::of(&cards(&["Ad", "3c", "6s"])), None);        // That's 10
+    assert_eq!(Declaration::of(&cards(&["Ad", "7c", "7s"])), Some(Declaration::Low)); // Matta as an ace
+    assert_eq!(Declaration::of(&cards(&["Rd", "Rc", "Rs"])), Some(Declaration::Tris));
+    assert_eq!(Declaration::of(&cards(&["Rd", "7c", "Rs"])), Some(Declaration::Tris));
+    assert_eq!(Declaration::of(&cards(&["Ad", "Ac", "As"])), Some(Declaration::Tris)); // Better than low
+    assert_eq!(Declaration::of(&cards(&["Ad", "Ac"])), None);
+
+    assert!(has_grande(&Deck(cards(&["Fd", "Cd", "Rd"]).into())));
+    assert!(!has_grande(&Deck(cards(&["Fd", "Cd", "Rc"]).into())));
+    assert_eq!(piccola(&Deck(cards(&["Ad", "2d", "3d", "5d"]).into())), Some(3));
+    assert_eq!(piccola(&Deck(cards(&["Ad", "2d", "3d", "4d", "5d", "6d", "7d"]).into())), Some(6));
+    assert_eq!(piccola(&Deck(cards(&["Ad", "2d"]).into())), None);
+
+    // Captures by 15, the single card rule only holds for captures by value
+    let mut m = dealt_match(ScopaRules::cirulla(), &["6d", "Rc"], &["4b", "2c", "5s", "6s"]);
+    let legal = m.legal_moves();
+    assert!(legal.contains(&Play::Take(card("6d"), cards(&["4b", "5s"]))));  // 6 + 4 + 5
+    assert!(legal.contains(&Play::Take(card("6d"), cards(&["6s"]))));
+    assert!(!legal.contains(&Play::Take(card("6d"), cards(&["4b", "2c"])))); // 6s must be taken
+    assert!(legal.contains(&Play::Take(card("Rc"), cards(&["5s"]))));
+    m.play(&Play::Take(card("Rc"), cards(&["5s"]))).unwrap();
+
+    // Declarations are made as hands are dealt and scored with the match
+    let mut m = Match::new_with_rules(&mut seeded_rng(0), ScopaRules::cirulla());
+    m.events.clear();
+    m.players[0].curr_hand = vec![];
+    m.players[1].curr_hand = vec![];
+    m.players[0].pile = Deck(cards(&["Fd", "Cd", "Rd", "Ad", "2d", "3d", "4d"]).into());
+    m.deck = Deck(cards(&["5b", "Rs", "2b", "7c", "Ab", "Rc"]).into()); // Dealt from the back
+    m.deal_hands();
+    assert_eq!(m.events.len(), 2);
+    assert_eq!((m.declared(0), m.declared(1)), (10, 3));
+    m.deck = Deck::default();
+    for p in &mut m.players { p.curr_hand.clear() }
+    let tally = m.tally_final_points();
+    assert_eq!((tally.grande, tally.piccola), (Some(0), Some((0, 4))));
+    assert_eq!(tally.team(0).unwrap().declared, 10);
+    assert!(tally.points(0) >= 10 + 5 + 4);
+
+    // A table adding up to 15 goes to the dealer
+    m.table = Deck(cards(&["Rb", "5c"]).into());
+    m.dealer_takes_fifteens();
+    assert_eq!((m.players[1].scope, m.table.len()), (1, 0));
+}
+
+#[test]
+fn cirulla_games_play_out() {
+    use rand::seq::SliceRandom;
+    for seed in 0..5 {
+        let mut rng = seeded_rng(seed);
+        let mut game = Game::with_rules(seed, ScopaRules::cirulla());
+        let outcome = play_out(&mut game, 10_000, |_, moves| moves.choose(&mut rng).cloned()).unwrap();
+        assert!(outcome.is_some());
+        assert!(game.points.iter().any(|&p| p >= 51));
+    }
+}
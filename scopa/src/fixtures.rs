@@ -0,0 +1,22 @@
+//! Tables set up by hand for the tests. Most tests need a couple of named cards and a match dealt
+//! a particular way, so they're written once here rather than in every test.
+
+use cards_core::*;
+
+use crate::*;
+
+pub(crate) fn card(s: &str) -> Card {
+    s.parse().unwrap()
+}
+
+pub(crate) fn cards(cs: &[&str]) -> Vec<Card> {
+    cs.iter().map(|s| card(s)).collect()
+}
+
+/// Dealt as `rules` say, then seat 0 is given `hand` and the table is set to `table`
+pub(crate) fn dealt_match(rules: ScopaRules, hand: &[&str], table: &[&str]) -> Match {
+    let mut m = Match::new_with_rules(&mut seeded_rng(0), rules);
+    m.players[0].curr_hand = cards(hand);
+    m.table = Deck(cards(table).into());
+    m
+}
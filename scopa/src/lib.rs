@@ -1,134 +1,323 @@
 use std::fmt::{Display, Formatter};
 use std::fmt::Debug;
 use std::cmp::Ordering;
+use std::str::FromStr;
 
 mod parse_move;
 use crate::parse_move::*;
+mod view;
+pub use view::*;
+mod rules;
+pub use rules::*;
+mod cirulla;
+pub use cirulla::*;
+mod bot;
+pub use bot::*;
+mod net;
+pub use net::*;
+mod record;
+pub use record::*;
+mod undo;
+pub use undo::*;
+#[cfg(test)]
+mod fixtures;
+#[cfg(test)]
+use fixtures::*;
 use cards_core::*;
 
+/// Scopa is played with any of the 40-card decks
+pub const DECKS: [DeckKind; 13] = DeckKind::FORTY_CARDS;
+
+/// Place at the table in the current match, in playing order: seat 0 plays first and the last
+/// seat is the one who dealt (the shuffler)
+pub type Seat = usize;
+/// See `Layout::teams`
+pub type Team = usize;
+
 #[derive(Clone, Debug, Default)]
 pub struct Player {
-    pub curr_hand: Vec<Card>, // Three or less held cards
+    pub curr_hand: Vec<Card>, // Held cards, three or less in plain scopa
     pub pile: Deck,           // Cards that they've won
     pub scope: usize,         // nº of scope obtained
 }
 
+/// Who's who, by colour. The first `Layout::seats` of `PlayerKind::ALL` play, sitting around
+/// the table in that order
 #[derive(Clone, Debug, Default, Copy, PartialEq, Eq)]
 pub enum PlayerKind {
     #[default]
     Purple,
-    Green
+    Green,
+    Orange,
+    Blue,
+    Red,
+    Yellow,
 }
 #[derive(Clone, Debug)]
 pub struct Game {
-    pub purple_points: usize, // Host, probably
-    pub green_points: usize,
+    pub points: Vec<usize>,   // Of each team
     pub curr_match: Match,
-    pub who_is_first: PlayerKind,
-    pub who_won_last_round: Turn,
+    pub players: Vec<PlayerKind>, // In the order they sit, `Layout::teams` says who's partnered with who
+    pub first_player: usize,  // Index in `players` of who plays first this match, the one before them dealt
+    pub who_won_last_round: Seat,
     pub last_move: Option<Move>,
+    pub history: GameRecord,  // Every deal and move so far
+    pub seed: u64,            // Every match of the game is dealt from this
+    rng: SeededRng,
+    pub style: CardStyle,
+    pub deck_kind: DeckKind,  // Only changes how cards are named
 }
 
 #[derive(Clone, Debug)]
 pub struct Move {
-    pub turn: Turn,
+    pub turn: Seat,
     pub card_played: Card,
     pub cards_taken: Option<Vec<Card>>,
 }
 
+/// A move in terms of cards rather than of positions in the hand and on the table
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Play {
+    /// Put a card from the hand on the table
+    Place(Card),
+    /// Take these cards from the table with a card from the hand. An ace must take all of them
+    Take(Card, Vec<Card>),
+}
+
+/// Anything `make_move` can be given: a `Play`, or one written out as `0;1+2`, `t0`... (see the
+/// README)
+pub trait IntoPlay<'a> {
+    /// The play it stands for in `m`, whose turn it is. It's not checked to be legal yet
+    fn into_play(self, m: &Match) -> Result<Play, MoveError<'a>>;
+}
+
 #[derive(Clone, Debug)]
 pub struct Match {
-    pub turn: Turn,
-    pub player_first: Player,
-    pub player_shuffler: Player,
+    pub turn: Seat,
+    pub players: Vec<Player>, // One per seat
+    pub teams: Vec<Team>,     // Team of each seat
     pub deck: Deck,
     pub table: Deck,
+    pub rules: ScopaRules,
+    pub events: Vec<Event>,   // Declarations and such, see `Mode::Cirulla`
+    pub moves: Vec<Move>,     // Made so far this match, in order
+    undo_stack: Vec<Snapshot>, // From before each of `moves`
+    redo_stack: Vec<Play>,    // Undone, the last one first
+    seen: usize,              // How many of `moves` the next player has seen, see `Match::mark_seen`
 }
 
-#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
-pub enum Turn {
-    #[default]
-    First,
-    Shuffler
+/// What a seat, or a whole team, took in a match
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Haul {
+    pub cards: usize,
+    pub denari: usize,
+    pub scope: usize,
+    pub primiera: Option<usize>, // `None` if it's missing a suit
+    pub declared: usize,         // Points for declarations, in cirulla
 }
 
 #[derive(Clone, Debug, Default)]
 pub struct PointTally {
-    scope_first: usize,
-    scope_shuf: usize,
-    num_cards: Option<Turn>,
-    num_denari: Option<Turn>,
-    sette_bello: Turn,
-    re_bello: Turn,
-    napoli: Option<(Turn, usize)>,
-    primiera: Option<Turn>,
+    seats: Vec<Haul>,
+    teams: Vec<Haul>,
+    seat_teams: Vec<Team>, // Team of each seat
+    num_cards: Option<Team>,
+    num_denari: Option<Team>,
+    sette_bello: Option<Team>,
+    re_bello: Option<Team>,
+    napoli: Option<(Team, usize)>,
+    primiera: Option<Team>,
+    grande: Option<Team>,
+    piccola: Option<(Team, usize)>,
+    rules: ScopaRules,
 }
 
 impl PointTally {
-    pub fn first_points(&self) -> usize {
-        count_points(self, Turn::First)
+    /// What `team` made this match
+    pub fn points(&self, team: Team) -> usize {
+        let mut p = self.teams.get(team).map_or(0, |h| h.scope + h.declared);
+
+        p += [self.num_cards   == Some(team),
+              self.num_denari  == Some(team),
+              self.sette_bello == Some(team),
+              self.re_bello    == Some(team),
+              self.primiera    == Some(team),
+        ].into_iter().filter(|&b| b).count();
+
+        if let Some((napoli_team, amount)) = self.napoli {
+            if napoli_team == team { p += amount }
+        }
+        if self.grande == Some(team) { p += 5 }
+        if let Some((piccola_team, amount)) = self.piccola {
+            if piccola_team == team { p += amount }
+        }
+
+        p
     }
-    pub fn shuf_points(&self) -> usize {
-        count_points(self, Turn::Shuffler)
+    /// `team`'s primiera total, `None` if they're missing a suit
+    pub fn primiera_of(&self, team: Team) -> Option<usize> {
+        self.teams.get(team).and_then(|h| h.primiera)
     }
-}
-
-fn count_points(tally: &PointTally, turn: Turn) -> usize {
-    let mut p = 0;
-
-    if turn == Turn::First { p += tally.scope_first }
-    else { p += tally.scope_shuf }
-
-    p += [tally.num_cards   == Some(turn),
-          tally.num_denari  == Some(turn),
-          tally.sette_bello == turn,
-          tally.re_bello    == turn, 
-          tally.primiera    == Some(turn),
-    ].into_iter().filter(|&b| b).count();
-
-    if let Some((napoli_turn, amount)) = tally.napoli {
-        if napoli_turn == turn { p += amount }
+    /// Everything `team` took, all its members together
+    pub fn team(&self, team: Team) -> Option<&Haul> {
+        self.teams.get(team)
+    }
+    /// What the player at `seat` took on their own
+    pub fn seat(&self, seat: Seat) -> Option<&Haul> {
+        self.seats.get(seat)
+    }
+    /// Seats of everyone in `team`
+    pub fn members(&self, team: Team) -> Vec<Seat> {
+        (0..self.seat_teams.len()).filter(|&s| self.seat_teams[s] == team).collect()
     }
 
-    p
+    /// The breakdown, calling each team by its name in `names`. Points that aren't played with
+    /// (see `ScopaRules`) are left out
+    pub fn render(&self, names: &[String]) -> String {
+        let name = |t: Team| names.get(t).cloned().unwrap_or_else(|| format!("Team {t}"));
+        let nobody = |t: Option<Team>| t.map(name).unwrap_or("Nobody".to_string());
+        // Teams of several players also get how much each of them did, in seat order
+        let per_team = |value: fn(&Haul) -> usize| (0..self.teams.len())
+            .map(|t| match &self.members(t)[..] {
+                [] | [_] => format!("{} {}", name(t), value(&self.teams[t])),
+                members  => format!("{} {} ({})", name(t), value(&self.teams[t]),
+                                    members.iter().map(|&s| value(&self.seats[s]).to_string()).collect::<Vec<String>>().join("+")),
+            })
+            .collect::<Vec<String>>().join(", ");
+
+        let mut s = String::from("=================\n");
+        s.push_str(&format!("Scope:\t\t\t\t{},\n", per_team(|h| h.scope)));
+        s.push_str(&format!("Nº cards:\t\t\t{} ({}),\n", nobody(self.num_cards), per_team(|h| h.cards)));
+        s.push_str(&format!("Nº denari\t\t\t{} ({}),\n", nobody(self.num_denari), per_team(|h| h.denari)));
+        s.push_str(&format!("Sette bello:\t\t\t{},\n", nobody(self.sette_bello)));
+        if self.rules.re_bello {
+            s.push_str(&format!("Re bello:\t\t\t{},\n", nobody(self.re_bello)));
+        }
+        if self.rules.napoli {
+            s.push_str(&format!("Napoli:  \t\t\t{},\n", self.napoli.map(|(t, n)| format!("{} ({n})", name(t))).unwrap_or("Nobody".to_string())));
+        }
+        let totals = self.teams.iter()
+            .map(|h| h.primiera.map(|n| n.to_string()).unwrap_or("missing a suit".to_string()))
+            .collect::<Vec<String>>().join(" - ");
+        s.push_str(&format!("Primiera:\t\t\t{} ({totals})\n", nobody(self.primiera)));
+        if self.rules.mode == Mode::Cirulla {
+            s.push_str(&format!("Grande:  \t\t\t{},\n", nobody(self.grande)));
+            s.push_str(&format!("Piccola: \t\t\t{},\n", self.piccola.map(|(t, n)| format!("{} ({n})", name(t))).unwrap_or("Nobody".to_string())));
+            s.push_str(&format!("Declared:\t\t\t{}\n", per_team(|h| h.declared)));
+        }
+        s.push_str("=================");
+        s
+    }
 }
 
 impl Game {
     pub fn new() -> Game {
+        Self::from_seed(random_seed())
+    }
+
+    /// Same seed, same sequence of deals
+    pub fn from_seed(seed: u64) -> Game {
+        Self::with_rules(seed, ScopaRules::default())
+    }
+
+    /// Same seed and layout, same sequence of deals
+    pub fn with_rules(seed: u64, rules: ScopaRules) -> Game {
+        let mut rng = seeded_rng(seed);
+        let layout = rules.layout;
+        let curr_match = Match::new_with_rules(&mut rng, rules);
         Game {
-            who_is_first: PlayerKind::Purple,
-            curr_match: Match::new(),
-            purple_points: 0,
-            green_points:  0,
-            who_won_last_round: Turn::First,
-            last_move: None
+            points: vec![0; layout.number_of_teams()],
+            history: GameRecord {
+                seed,
+                rules,
+                matches: vec![MatchRecord::dealt(&curr_match, 0)],
+                points: vec![0; layout.number_of_teams()],
+            },
+            curr_match,
+            players: PlayerKind::ALL[..layout.seats].to_vec(),
+            first_player: 0,
+            who_won_last_round: 0,
+            last_move: None,
+            seed,
+            rng,
+            style: CardStyle::default(),
+            deck_kind: DeckKind::default(),
         }
     }
 
-    pub fn make_move<'a>(&'a mut self, mov: &'a str) -> Result<Option<Move>, MoveError> {
+    /// Deal the next match from the game's rng, with the same rules
+    pub fn start_new_match(&mut self) {
+        let rules = *self.rules();
+        self.curr_match = Match::new_with_rules(&mut self.rng, rules);
+        self.curr_match.teams = (0..self.players.len())
+            .map(|seat| self.rules().layout.team_of(self.player_index(seat)))
+            .collect();
+        self.who_won_last_round = 0;
+        self.history.matches.push(MatchRecord::dealt(&self.curr_match, self.first_player));
+    }
+
+    pub fn rules(&self) -> &ScopaRules {
+        &self.curr_match.rules
+    }
+    /// Takes effect immediately, in the current match too. Changing the layout or the mode
+    /// (they change how cards are dealt) starts the whole game over, from the same seed
+    pub fn set_rules(&mut self, rules: ScopaRules) {
+        if rules.layout != self.rules().layout || rules.mode != self.rules().mode {
+            *self = Game { style: self.style, deck_kind: self.deck_kind, ..Game::with_rules(self.seed, rules) };
+        }
+        self.curr_match.rules = rules;
+        self.history.rules = rules;
+    }
+
+    pub fn make_move<'a>(&mut self, mov: impl IntoPlay<'a>) -> Result<Option<Move>, MoveError<'a>> {
         let m = self.curr_match.make_move(mov)?;
-        if let Some(move_made) = &m {
+        if let Some(move_made) = &m { self.record(move_made) }
+        Ok(m)
+    }
+    /// Like `make_move`, but typed
+    pub fn play(&mut self, play: &Play) -> Result<Move, MoveError<'static>> {
+        let m = self.curr_match.play(play)?;
+        self.record(&m);
+        Ok(m)
+    }
+    pub(crate) fn record(&mut self, move_made: &Move) {
+        if move_made.cards_taken.is_some() {
             self.who_won_last_round = move_made.turn;
         }
-        Ok(m)
+        self.last_move = Some(move_made.clone());
+        self.history.matches.last_mut().expect("A game always has a match").moves.push(move_made.clone());
     }
-    pub fn toggle_turn(&mut self) {
-        self.curr_match.turn.toggle_turn()
+    pub fn next_turn(&mut self) {
+        self.curr_match.next_turn()
     }
+    /// The winning team's name and points, and the points of whoever came closest
     pub fn winner(&self) -> Option<(String, usize, usize)> {
-        let (purp, grep) = (self.purple_points, self.green_points);
-        let (purple_win, green_win) = (Some((purple_text(), purp, grep)), Some((green_text(),  grep, purp)));
-
-        match (purp, grep) {
-            (0..=20, 0..=20)  => None,
-            (21..,   0..=20)  => purple_win,
-            (0..=20, 21..  )  => green_win,
-            (p, g)            => match p.cmp(&g) {
-                Ordering::Less    => green_win,
-                Ordering::Equal   => None,
-                Ordering::Greater => purple_win,
-            }
-        }
+        let team = self.winning_team()?;
+        let runner_up = self.points.iter().enumerate()
+            .filter(|&(t, _)| t != team)
+            .map(|(_, &p)| p)
+            .max().unwrap_or(0);
+        Some((self.team_name(team), self.points[team], runner_up))
+    }
+    /// The highest score, if it reached the target and nobody else has it too
+    fn winning_team(&self) -> Option<Team> {
+        best(&self.points).filter(|&t| self.points[t] >= self.rules().target_score)
+    }
+
+    /// Which team, if any, has taken all ten denari in the match that just ended. Takes
+    /// preference over points
+    pub fn full_napoli(&self) -> Option<Team> {
+        if !self.curr_match.is_over() || !self.rules().napoli { return None; }
+        (0..self.points.len()).find(|&t| has_full_napoli(&self.curr_match.team_pile(t)))
+    }
+
+    /// Add a finished match's points to the running score, returns what each team made
+    pub fn add_points(&mut self, tally: &PointTally) -> Vec<usize> {
+        let made: Vec<usize> = (0..self.points.len()).map(|t| tally.points(t)).collect();
+        for (points, m) in self.points.iter_mut().zip(&made) { *points += m }
+        self.history.matches.last_mut().expect("A game always has a match").tally = Some(tally.clone());
+        self.history.points = self.points.clone();
+        made
     }
 
     pub fn is_match_over(&mut self) -> Option<PointTally> {
@@ -141,35 +330,68 @@ impl Game {
     }
 
     pub fn give_table_to_last_taker(&mut self) {
-        let player: &mut Player = match self.who_won_last_round {
-            Turn::First    => &mut self.curr_match.player_first,
-            Turn::Shuffler => &mut self.curr_match.player_shuffler,
-        };
+        let player: &mut Player = &mut self.curr_match.players[self.who_won_last_round];
 
         for _ in 0..self.curr_match.table.len() {
             player.pile.push_to_top(self.curr_match.table.take_from_top().unwrap());
         }
     }
 
+    /// Index in `players` of whoever is sitting at `seat` this match
+    fn player_index(&self, seat: Seat) -> usize {
+        (self.first_player + seat) % self.players.len()
+    }
+    pub fn player_at(&self, seat: Seat) -> PlayerKind {
+        self.players[self.player_index(seat)]
+    }
     pub fn color_playing(&self) -> PlayerKind {
-        use PlayerKind as PK;
-        match (self.curr_match.turn, self.who_is_first) {
-            (Turn::First,    PK::Purple) => PK::Purple,
-            (Turn::First,    PK::Green)  => PK::Green,
-            (Turn::Shuffler, PK::Purple) => PK::Green,
-            (Turn::Shuffler, PK::Green)  => PK::Purple,
-        }
+        self.player_at(self.curr_match.turn)
     }
-    pub fn toggle_whose_first(&mut self) {
-        self.who_is_first = !self.who_is_first;
+    /// Which seat `player` has this match
+    pub fn seat_of(&self, player: PlayerKind) -> Seat {
+        let i = self.players.iter().position(|&p| p == player).expect("Only those at the table have a seat");
+        (i + self.players.len() - self.first_player) % self.players.len()
     }
-    pub fn print_cards_of_curr_player(&self) {
-        let cards = match self.curr_match.turn {
-            Turn::First    => &self.curr_match.player_first.curr_hand,
-            Turn::Shuffler => &self.curr_match.player_shuffler.curr_hand,
+    /// Pick who plays first in the next match, as `ScopaRules::rotation` says. Call it before
+    /// `start_new_match`, while the last match's seats still hold
+    pub fn rotate_dealer(&mut self) {
+        self.first_player = match self.rules().rotation {
+            Rotation::Around    => (self.first_player + 1) % self.players.len(),
+            Rotation::Fixed     => self.first_player,
+            Rotation::LastTaker => self.player_index(self.who_won_last_round),
         };
+    }
+
+    /// The team `player` plays in, the same every match
+    pub fn team_of(&self, player: PlayerKind) -> Team {
+        let i = self.players.iter().position(|&p| p == player).expect("Only those at the table are in a team");
+        self.rules().layout.team_of(i)
+    }
+    /// Everyone in `team`, e.g. `Purple & Orange`
+    pub fn team_name(&self, team: Team) -> String {
+        self.rules().layout.members(team).into_iter()
+            .map(|i| self.players[i].render(self.style))
+            .collect::<Vec<String>>().join(" & ")
+    }
+    pub fn team_names(&self) -> Vec<String> {
+        (0..self.points.len()).map(|t| self.team_name(t)).collect()
+    }
 
-        let s: String = cards.iter().enumerate().map(|(i, c)| format!("{c}({i})")).collect::<Vec<String>>().join(" ; ");
+    /// `mov`, saying who made it rather than from what seat
+    pub fn render_move(&self, mov: &Move) -> String {
+        mov.describe(&self.player_at(mov.turn).render(self.style), self.style, self.deck_kind)
+    }
+    /// `event`, saying who it was rather than what seat
+    pub fn render_event(&self, event: &Event) -> String {
+        event.describe(&self.player_at(event.seat()).render(self.style), self.style, self.deck_kind)
+    }
+
+    pub fn print_cards_of_curr_player(&self) {
+        let cards = &self.curr_match.curr_player().curr_hand;
+
+        let s: String = cards.iter().enumerate()
+            .map(|(i, c)| format!("{}({i})", self.style.render(c, self.deck_kind)))
+            .collect::<Vec<String>>().join(" ; ");
         println!("{s}");
     }
 }
@@ -178,195 +400,314 @@ impl Game {
 
 impl Match {
     pub fn new() -> Match {
-        let mut deck = Card::shuffled_basic_deck();
+        Self::new_with(&mut rand::thread_rng())
+    }
 
-        let mut player_first = Player::default();
-        let mut player_shuffler = Player::default();
+    pub fn from_seed(seed: u64) -> Match {
+        Self::new_with(&mut seeded_rng(seed))
+    }
 
-        for _ in 0..3 {
-            let (c1, c2) = (deck.take_from_top().unwrap(), deck.take_from_top().unwrap());
+    pub fn new_with<R: rand::Rng + ?Sized>(rng: &mut R) -> Match {
+        Self::new_with_rules(rng, ScopaRules::default())
+    }
 
-            player_first.curr_hand.push(c1);
-            player_shuffler.curr_hand.push(c2);
-        }
+    /// Dealt as `rules.layout` says, seat `n` is on team `rules.layout.teams[n]`
+    pub fn new_with_rules<R: rand::Rng + ?Sized>(rng: &mut R, rules: ScopaRules) -> Match {
+        let layout = rules.layout;
+        let mut m = Match {
+            turn: 0,
+            players: vec![Player::default(); layout.seats],
+            teams: layout.teams[..layout.seats].to_vec(),
+            deck: Card::shuffled_basic_deck_with(rng),
+            table: Deck::default(),
+            rules,
+            events: vec![],
+            moves: vec![],
+            undo_stack: vec![],
+            redo_stack: vec![],
+            seen: 0,
+        };
+        m.deal_hands();
 
-        let mut table = Deck::default();
-        for _ in 0..4 {
-            let c = deck.take_from_top().unwrap();
-            table.push_to_top(c);
+        for _ in 0..layout.table_size {
+            let c = m.deck.take_from_top().unwrap();
+            m.table.push_to_top(c);
         }
+        m.dealer_takes_fifteens();
+        m
+    }
 
-        Match { player_first, player_shuffler, deck, table, turn: Turn::First }
+    /// `hand_size` cards to everyone, one at a time going around the table
+    fn deal_hands(&mut self) {
+        for _ in 0..self.rules.layout.hand_size {
+            for player in &mut self.players {
+                if let Some(c) = self.deck.take_from_top() { player.curr_hand.push(c) }
+            }
+        }
+        self.declare_hands();
     }
 
     pub fn is_over(&self) -> bool {
-        self.deck.is_empty()
-            && self.player_first.curr_hand.is_empty()
-            && self.player_shuffler.curr_hand.is_empty()
+        self.deck.is_empty() && self.players.iter().all(|p| p.curr_hand.is_empty())
+    }
+
+    pub fn curr_player(&self) -> &Player {
+        &self.players[self.turn]
+    }
+    pub fn next_turn(&mut self) {
+        self.turn = (self.turn + 1) % self.players.len();
+    }
+
+    /// Everything the players in `team` have taken
+    pub fn team_pile(&self, team: Team) -> Deck {
+        let mut pile = Deck::default();
+        for (player, _) in self.players.iter().zip(&self.teams).filter(|(_, &t)| t == team) {
+            pile.extend(player.pile.iter().copied());
+        }
+        pile
     }
 
     /// Returns a Result, that means
-    /// - Ok(Option<Turn>): Is Some if the last move was a take, None if it was placing on the table (for keeping track of the last person to take)
+    /// - Ok(Option<Move>): The move that was made
     /// - Err(...): Read the docs for MoveError
-    pub fn make_move<'a>(&'a mut self, input: &'a str) -> Result<Option<Move>, MoveError> {
+    ///
+    /// Takes either a string or one of `legal_moves`
+    pub fn make_move<'a>(&mut self, mov: impl IntoPlay<'a>) -> Result<Option<Move>, MoveError<'a>> {
+        let play = mov.into_play(self)?;
+        Ok(Some(self.play(&play)?))
+    }
+
+    /// What `input` means with the current hand and table
+    fn resolve<'a>(&self, input: &'a str) -> Result<Play, MoveError<'a>> {
         let mov = Self::parse_move(input)?;
-        let last_move;
 
-        let player = match self.turn {
-            Turn::First => &mut self.player_first,
-            Turn::Shuffler => &mut self.player_shuffler,
+        let hand = &self.curr_player().curr_hand;
+        let hand_card: Card = match mov.from {
+            Pick::Index(i) => *hand.get(i).ok_or(MoveError::OutOfRangeOfHand)?,
+            Pick::Card(c) if hand.contains(&c) => c,
+            Pick::Card(c) => return Err(MoveError::CardNotInHand(c)),
         };
 
-        let hand_card: Card = *player.curr_hand.get(mov.from)
-            .ok_or(MoveError::OutOfRangeOfHand)?;
-
-        if let Some(to_indices) = mov.to {
-            let table_cards: Vec<&Card> = to_indices.iter()
-                .map(|&i| self.table.get(i))
-                .collect::<Option<Vec<&Card>>>()
-                .ok_or(MoveError::OutOfRangeOfTable)?;
-
-            if hand_card.number == CardNum::Numeric(1) {
-                // We have an ace, we get everything (including itself)
-
-                last_move = Some(Move {
-                    card_played: hand_card,
-                    cards_taken: Some(self.table.iter().map(|c| c.clone()).collect()),
-                    turn: self.turn,
-                });
-
-                for _ in 0..self.table.len() {
-                    player.pile.push_to_top(self.table.take_from_top().unwrap());
+        let play = match mov.to {
+            Destination::Table => Play::Place(hand_card),
+            Destination::Whichever => {
+                let mut takes = self.takes_with(&hand_card);
+                match takes.len() {
+                    0 => Play::Place(hand_card),
+                    1 => takes.remove(0),
+                    _ => return Err(MoveError::Ambiguous(takes)),
                 }
-                player.pile.push_to_top(hand_card); // Don't forget the ace
+            },
+            Destination::Taking(picks) => {
+                let table_cards: Vec<Card> = picks.into_iter()
+                    .map(|p| match p {
+                        Pick::Index(i) => self.table.get(i).copied().ok_or(MoveError::OutOfRangeOfTable),
+                        Pick::Card(c) if self.table.contains(&c) => Ok(c),
+                        Pick::Card(c) => Err(MoveError::CardNotOnTable(c)),
+                    })
+                    .collect::<Result<Vec<Card>, MoveError>>()?;
+
+                if self.sweeps(&hand_card) {
+                    // We have an ace, we get everything no matter what was picked
+                    Play::Take(hand_card, self.table.iter().copied().collect())
+                } else {
+                    Play::Take(hand_card, table_cards)
+                }
+            },
+        };
 
-                // Remove it from hand
-                remove_elem_from_vec(&mut player.curr_hand, hand_card);
+        Ok(play)
+    }
 
-            } else if hand_card.value() == table_cards.iter().map(|c| c.value()).sum() {
-                for card in &table_cards {
-                    player.pile.push_to_top(**card);
-                    player.pile.push_to_top(hand_card);
+    /// Make `play` as whoever's turn it is (the turn isn't toggled). Nothing changes if it's illegal
+    pub fn play(&mut self, play: &Play) -> Result<Move, MoveError<'static>> {
+        let hand_card = match play {
+            Play::Place(card) | Play::Take(card, _) => *card,
+        };
+        if !self.curr_player().curr_hand.contains(&hand_card) {
+            return Err(MoveError::CardNotInHand(hand_card));
+        }
+        let sweeps = self.sweeps(&hand_card);
+        if let (Play::Place(_), true, false) = (play, sweeps, self.table.is_empty()) {
+            // Placing an ace is taking everything, `N;` and `tN` are the same
+            return self.play(&Play::Take(hand_card, self.table.iter().copied().collect()));
+        }
+        if let (Play::Place(_), true) = (play, self.rules.must_take) {
+            if !self.takes_with(&hand_card).is_empty() { return Err(MoveError::MustTake(hand_card)); }
+        }
+        let cards_left: usize = self.players.iter().map(|p| p.curr_hand.len()).sum();
+        let is_last_card = self.deck.is_empty() && cards_left == 1;
+
+        let mut table = self.table.clone();
+        let cards_taken = match play {
+            Play::Place(_) => {
+                table.push_to_top(hand_card);
+                None
+            },
+            Play::Take(_, taken) => {
+                if taken.is_empty() { return Err(MoveError::NothingTaken); }
+                for card in taken {
+                    let i = table.iter().position(|c| c == card).ok_or(MoveError::CardNotOnTable(*card))?;
+                    table.remove(i);
                 }
-                last_move = Some(Move {
-                    card_played: hand_card,
-                    cards_taken: Some(table_cards.iter().map(|&c| c.clone()).collect()),
-                    turn: self.turn,
-                });
 
-                for i in to_indices.into_iter().rev() { self.table.remove(i); } // Remove them from the table
-                remove_elem_from_vec(&mut player.curr_hand, hand_card);
-
-                if self.table.is_empty() { // Do we have a scopa (non-ace)?
-                    player.scope += 1;
+                let sum: usize = taken.iter().map(|c| c.value()).sum();
+                let by_value = self.rules.capture.by_value() && hand_card.value() == sum;
+                let by_fifteen = self.rules.capture.by_fifteen() && hand_card.value() + sum == 15;
+                if sweeps {
+                    if !table.is_empty() { return Err(MoveError::AceTakesEverything); }
+                } else if !by_value && !by_fifteen {
+                    return Err(MoveError::MismatchedValues);
+                } else if let (true, 2.., Some(single)) = (by_value, taken.len(), self.single_card_take(&hand_card)) {
+                    return Err(MoveError::MustTakeSingleCard(single));
                 }
+                Some(taken.clone())
+            },
+        };
 
-            } else {
-                return Err(MoveError::MismatchedValues);
-            }
-        } else {
-            // Place on table
-            // TODO: make asso piglia tutto do the thingy instead of placing it when placing a card on the table (`N;` vs `tN` should be identical with an ace)
-            last_move = Some(Move {
-                card_played: hand_card,
-                cards_taken: None,
-                turn: self.turn,
-            });
-            self.table.push_to_top(hand_card);
-            remove_elem_from_vec(&mut player.curr_hand, hand_card);
-        }
-
-        if player.curr_hand.is_empty() && !self.deck.is_empty() {
-            // Redeal three cards from the deck
-            for _ in 0..3 {
-                let c = self.deck.take_from_top().unwrap();
-                player.curr_hand.push(c);
+        // It's legal, now we can actually make it
+        self.save_for_undo();
+        let turn = self.turn;
+        let player = &mut self.players[turn];
+        remove_elem_from_vec(&mut player.curr_hand, hand_card);
+        if let Some(taken) = &cards_taken {
+            for card in taken { player.pile.push_to_top(*card); }
+            player.pile.push_to_top(hand_card);
+
+            let counts = (!sweeps || self.rules.ace_sweep_is_scopa) && (!is_last_card || self.rules.last_hand_scopa);
+            if table.is_empty() && counts { // Do we have a scopa?
+                player.scope += 1;
             }
         }
-        Ok(last_move)
-    }
+        self.table = table;
 
-    fn tally_final_points(&self) -> PointTally {
-        let mut tally = PointTally::default();
+        if self.players.iter().all(|p| p.curr_hand.is_empty()) {
+            // Redeal from the deck, if there's anything left
+            self.deal_hands();
+        }
 
-        let fir = &self.player_first.pile;
-        let shuf = &self.player_shuffler.pile;
+        let made = Move { turn, card_played: hand_card, cards_taken };
+        self.moves.push(made.clone());
+        Ok(made)
+    }
 
-        tally.scope_first = self.player_first.scope;
-        tally.scope_shuf = self.player_shuffler.scope;
+    /// A table card of the same value as `card`, if there is one. When there is, `card` may
+    /// only take cards of its own value (presa obbligatoria di una carta)
+    fn single_card_take(&self, card: &Card) -> Option<Card> {
+        if !self.rules.capture.by_value() { return None; }
+        self.table.iter().find(|c| c.value() == card.value()).copied()
+    }
 
-        // Number of cards
-        match fir.len().cmp(&shuf.len()) {
-            Ordering::Greater  => tally.num_cards = Some(Turn::First),
-            Ordering::Equal    => tally.num_cards = None,
-            Ordering::Less     => tally.num_cards = Some(Turn::Shuffler),
-        }
+    /// Whether `card` is an ace that takes the whole table, see `ScopaRules::asso_piglia_tutto`
+    fn sweeps(&self, card: &Card) -> bool {
+        let is_ace = |c: &Card| c.number == CardNum::Numeric(1);
+        is_ace(card) && self.rules.asso_piglia_tutto && !(self.rules.ace_takes_ace && self.table.iter().any(is_ace))
+    }
 
-        // Number of Denari (monee monee monee)
-        match fir.iter().filter(|c| c.suit == Suit::Denari).count()
-            .cmp(&shuf.iter().filter(|c| c.suit == Suit::Denari).count()) {
-            Ordering::Greater  => tally.num_denari = Some(Turn::First),
-            Ordering::Equal    => tally.num_denari = None,
-            Ordering::Less     => tally.num_denari = Some(Turn::Shuffler),
+    /// Every play the current player could make, in hand order: placing any card (unless it
+    /// has to take, see `ScopaRules::must_take`), taking any combination of table cards that adds
+    /// up to a card (or to 15 with it, see `Capture`) or sweeping the table with an ace. When a
+    /// single card can be taken, combinations aren't offered (see `MoveError::MustTakeSingleCard`)
+    ///
+    /// Any of them can be given to `make_move` or `play` as is
+    pub fn legal_moves(&self) -> Vec<Play> {
+        let mut plays = vec![];
+        for &card in &self.curr_player().curr_hand {
+            let takes = self.takes_with(&card);
+            // A sweeping ace can only be placed if there's nothing to take
+            if takes.is_empty() || !(self.rules.must_take || self.sweeps(&card)) {
+                plays.push(Play::Place(card));
+            }
+            plays.extend(takes);
         }
+        plays
+    }
 
-        // Who has 7 bello
-        if fir.iter().any(|c| c == &Card::denari(7)) {
-            tally.sette_bello = Turn::First;
-        } else {
-            tally.sette_bello = Turn::Shuffler;
+    /// Every legal way of taking with `card`, none if it can only be placed
+    fn takes_with(&self, card: &Card) -> Vec<Play> {
+        let table: Vec<Card> = self.table.iter().copied().collect();
+        if self.sweeps(card) {
+            return if table.is_empty() { vec![] } else { vec![Play::Take(*card, table)] };
         }
 
-        // Who has king bello
-        if fir.iter().any(|c| c == &Card::denari(10 /* Re */)) {
-            tally.re_bello = Turn::First;
-        } else {
-            tally.re_bello = Turn::Shuffler;
+        let mut takes = vec![];
+        if self.single_card_take(card).is_some() {
+            takes.extend(table.iter().filter(|c| c.value() == card.value()).map(|c| Play::Take(*card, vec![*c])));
+        } else if self.rules.capture.by_value() {
+            takes.extend(combinations_adding_to(&table, card.value()).into_iter().map(|taken| Play::Take(*card, taken)));
+        }
+        if self.rules.capture.by_fifteen() && card.value() < 15 {
+            takes.extend(combinations_adding_to(&table, 15 - card.value()).into_iter().map(|taken| Play::Take(*card, taken)));
         }
+        takes
+    }
 
-        // Napoli 
-        if let Some(p) = check_napoli(fir) {
-            tally.napoli = Some((Turn::First, p));
-        } else if let Some(p) = check_napoli(shuf) {
-            tally.napoli = Some((Turn::Shuffler, p));
-        } else {
-            tally.napoli = None;
-        }
-
-        // Primiera (7s thing (just counting))
-        let mut i = 7;
-        while i > 0 {
-            match cards_with_value(i, fir).cmp(&cards_with_value(i, shuf)) {
-                Ordering::Greater => {
-                    tally.primiera = Some(Turn::First);
-                    break;
-                },
-                Ordering::Equal   => i -= 1,
-                Ordering::Less    => {
-                    tally.primiera = Some(Turn::Shuffler);
-                    break;
-                }
-            }
+    fn tally_final_points(&self) -> PointTally {
+        let teams = self.rules.layout.number_of_teams();
+        let piles: Vec<Deck> = (0..teams).map(|t| self.team_pile(t)).collect();
+        let who_has = |card: Card| piles.iter().position(|p| p.contains(&card));
+        let haul = |pile: &Deck, scope: usize, declared: usize| Haul {
+            cards: pile.len(),
+            // Monee monee monee
+            denari: pile.iter().filter(|c| c.suit == Suit::Denari).count(),
+            scope,
+            primiera: primiera(pile),
+            declared,
+        };
+
+        let seats: Vec<Haul> = self.players.iter().enumerate()
+            .map(|(seat, p)| haul(&p.pile, p.scope, self.declared(seat)))
+            .collect();
+        let teams: Vec<Haul> = piles.iter().enumerate()
+            .map(|(t, pile)| {
+                let members = (0..self.players.len()).filter(|&s| self.teams[s] == t);
+                haul(pile, members.clone().map(|s| seats[s].scope).sum(), members.map(|s| seats[s].declared).sum())
+            })
+            .collect();
+        let cirulla = self.rules.mode == Mode::Cirulla;
+
+        PointTally {
+            num_cards: best(&teams.iter().map(|h| h.cards).collect::<Vec<usize>>()),
+            num_denari: best(&teams.iter().map(|h| h.denari).collect::<Vec<usize>>()),
+            sette_bello: who_has(Card::denari(7)),
+            re_bello: who_has(Card::denari(10 /* Re */)).filter(|_| self.rules.re_bello),
+            napoli: piles.iter().enumerate()
+                .find_map(|(t, p)| check_napoli(p).map(|n| (t, n)))
+                .filter(|_| self.rules.napoli),
+            // Primiera: the best card of each suit, added up. Lacking a suit (`None`) loses to any total
+            primiera: best(&teams.iter().map(|h| h.primiera).collect::<Vec<Option<usize>>>()),
+            grande: piles.iter().position(has_grande).filter(|_| cirulla),
+            piccola: piles.iter().enumerate()
+                .find_map(|(t, p)| piccola(p).map(|n| (t, n)))
+                .filter(|_| cirulla),
+            seats,
+            teams,
+            seat_teams: self.teams.clone(),
+            rules: self.rules,
         }
-        
-        tally
     }
 
-    fn parse_move(mov: &str) -> Result<ParsedMove, MoveError> {
+    fn parse_move(mov: &str) -> Result<ParsedMove, MoveError<'_>> {
         let (_, result) = parse_move_internal(mov).map_err(MoveError::ParseError)?;
         Ok(result)
     }
 }
 
 pub fn has_full_napoli(pila: &Deck) -> bool {
-    (1..=10).all(|i| pila.contains(&Card::denari(i)))
+    let denari: CardSet = (1..=10).map(Card::denari).collect();
+    CardSet::from(pila).contains_all(denari)
 }
 
-fn cards_with_value(target_value: usize, cards: &Deck) -> usize {
-    cards.iter().filter(|c| c.value() == target_value).count()
+/// Primiera total of a pile: the best card of each suit by the 21/18/16/15/14/13/12/10 table
+/// (see `cards_core::Primiera`), `None` if a suit is missing altogether
+pub fn primiera(pile: &Deck) -> Option<usize> {
+    [Suit::Denari, Suit::Coppe, Suit::Bastoni, Suit::Spade].iter()
+        .map(|&suit| pile.iter().filter(|c| c.suit == suit).map(|c| Primiera.rank(c) as usize).max())
+        .sum()
 }
 
 fn check_napoli(pila: &Deck) -> Option<usize> {
+    let pila = CardSet::from(pila);
     if [1, 2, 3].iter().all(|&i| pila.contains(&Card::denari(i))) {
         if !pila.contains(&Card::denari(4)) {
             Some(1)
@@ -381,14 +722,73 @@ fn check_napoli(pila: &Deck) -> Option<usize> {
     
 }
 
+/// Index of the strictly highest value, `None` if the highest is shared
+fn best<T: Ord>(values: &[T]) -> Option<usize> {
+    let max = values.iter().max()?;
+    let mut highest = values.iter().enumerate().filter(|&(_, v)| v == max);
+    match (highest.next(), highest.next()) {
+        (Some((i, _)), None) => Some(i),
+        _                    => None,
+    }
+}
+
+/// Every (non-empty) combination of `cards` whose values add up to `target`, in table order
+fn combinations_adding_to(cards: &[Card], target: usize) -> Vec<Vec<Card>> {
+    let Some((first, rest)) = cards.split_first() else { return vec![] };
+
+    let mut combinations = combinations_adding_to(rest, target);
+    match first.value().cmp(&target) {
+        Ordering::Equal   => combinations.push(vec![*first]),
+        Ordering::Less    => combinations.extend(combinations_adding_to(rest, target - first.value())
+                                                 .into_iter()
+                                                 .map(|mut c| { c.insert(0, *first); c })),
+        Ordering::Greater => {},
+    }
+    combinations
+}
+
 fn remove_elem_from_vec<T>(v: &mut Vec<T>, elem: T) where T: PartialEq {
     let index = v.iter().position(|x| x == &elem).unwrap();
     v.remove(index);
 }
 
+/// Comma separated, `-` if empty. Records and the network protocol write lists this way
+pub(crate) fn list<T: ToString>(items: &[T]) -> String {
+    if items.is_empty() { return "-".to_string(); }
+    items.iter().map(|i| i.to_string()).collect::<Vec<String>>().join(",")
+}
+
+/// A `list` of cards, in the Italian notation
+pub(crate) fn card_list(cards: &[Card]) -> String {
+    list(&cards.iter().map(|c| c.notation(Notation::Italian)).collect::<Vec<String>>())
+}
+
+/// Read back what `list` wrote
+pub(crate) fn parse_list<T: FromStr>(s: &str) -> Result<Vec<T>, String> {
+    if s == "-" { return Ok(vec![]); }
+    s.split(',')
+        .map(|i| i.parse().map_err(|_| format!("'{i}' isn't valid in '{s}'")))
+        .collect()
+}
+
 struct ParsedMove {
-    from: usize,
-    to: Option<Vec<usize>>
+    from: Pick,
+    to: Destination,
+}
+
+/// A card in the hand or on the table, as it was written down
+enum Pick {
+    Index(usize),
+    Card(Card),
+}
+
+enum Destination {
+    /// `tN`
+    Table,
+    /// `N;M+...`
+    Taking(Vec<Pick>),
+    /// Just `N`: take if there's a single way of doing it, place otherwise
+    Whichever,
 }
 
 #[derive(Debug)]
@@ -407,90 +807,164 @@ pub enum MoveError<'a> {
     OutOfRangeOfHand,
     /// At least one of the table indices was out of range 
     OutOfRangeOfTable,
+    /// The card to play isn't in the player's hand
+    CardNotInHand(Card),
+    /// A card to take isn't on the table (or was listed twice)
+    CardNotOnTable(Card),
+    /// A take that takes no cards
+    NothingTaken,
+    /// Aces sweep the table, they can't take only part of it
+    AceTakesEverything,
+    /// Tried to take several cards while this one, of the same value, is on the table: it's the
+    /// one that has to be taken
+    MustTakeSingleCard(Card),
+    /// Tried to place this card while it could take something, see `ScopaRules::must_take`
+    MustTake(Card),
+    /// Only the card to play was given, and it can take in all these ways
+    Ambiguous(Vec<Play>),
 }
 
-impl Display for Turn {
+impl Display for MoveError<'_> {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let name = |c: &Card| c.notation(Notation::Italian);
         match self {
-            Turn::First    => write!(f, "First"),
-            Turn::Shuffler => write!(f, "Shuffler"),
+            MoveError::ParseError(_)            => write!(f, "that's not a move, try e.g. `0;1+2`, `t0`, `7d;3c+4b` or `7d`"),
+            MoveError::InvalidDigit             => write!(f, "positions are single digits"),
+            MoveError::InvalidAce               => write!(f, "there's no ace to play"),
+            MoveError::MismatchedValues         => write!(f, "those cards don't add up to the one played"),
+            MoveError::AdditionDoesntCheckOut   => write!(f, "the addition doesn't check out"),
+            MoveError::OutOfRangeOfHand         => write!(f, "there's no card at that position in the hand"),
+            MoveError::OutOfRangeOfTable        => write!(f, "there's no card at that position on the table"),
+            MoveError::CardNotInHand(c)         => write!(f, "{} isn't in the hand", name(c)),
+            MoveError::CardNotOnTable(c)        => write!(f, "{} isn't on the table (or was listed twice)", name(c)),
+            MoveError::NothingTaken             => write!(f, "a take has to take something"),
+            MoveError::AceTakesEverything       => write!(f, "an ace takes the whole table"),
+            MoveError::MustTakeSingleCard(c)    => write!(f, "{} is on the table, it has to be taken on its own", name(c)),
+            MoveError::MustTake(c)              => write!(f, "{} can take, so it can't be placed", name(c)),
+            MoveError::Ambiguous(plays) => write!(f, "that could be any of {}, say which",
+                                                  plays.iter().map(Play::notation).collect::<Vec<String>>().join(", ")),
         }
     }
 }
 
-impl Display for Match {
-    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
-        
-        let s = format!(
+impl<'a> IntoPlay<'a> for &'a str {
+    fn into_play(self, m: &Match) -> Result<Play, MoveError<'a>> {
+        m.resolve(self)
+    }
+}
+impl<'a> IntoPlay<'a> for &'a String {
+    fn into_play(self, m: &Match) -> Result<Play, MoveError<'a>> {
+        m.resolve(self)
+    }
+}
+impl IntoPlay<'_> for Play {
+    fn into_play(self, _: &Match) -> Result<Play, MoveError<'static>> {
+        Ok(self)
+    }
+}
+impl IntoPlay<'_> for &Play {
+    fn into_play(self, _: &Match) -> Result<Play, MoveError<'static>> {
+        Ok(self.clone())
+    }
+}
+
+/// `First`, `Second`... for seats 0, 1...
+pub fn seat_name(seat: Seat) -> String {
+    const NAMES: [&str; MAX_SEATS] = ["First", "Second", "Third", "Fourth", "Fifth", "Sixth"];
+    NAMES.get(seat).map(|n| n.to_string()).unwrap_or_else(|| format!("Seat {seat}"))
+}
+
+impl Match {
+    pub fn render(&self, style: CardStyle, deck: DeckKind) -> String {
+        let deal_size = (self.players.len() * self.rules.layout.hand_size).max(1);
+        let hands: String = self.players.iter().enumerate()
+            .map(|(seat, p)| format!("{} has '{}' cards\n", seat_name(seat), p.curr_hand.len()))
+            .collect();
+        format!(
 "-------------------
 Turn: '{}'
 Deck has '{}' cards ('{}' turns left)
-First has '{}' cards
-Shuffler has '{}' cards
-Table has cards: '{}'
+{hands}Table has cards: '{}'
 -------------------",
-            self.turn,
-            self.deck.len(), self.deck.len() / 6,
-            self.player_first.curr_hand.len(),
-            self.player_shuffler.curr_hand.len(),
-            self.table.iter().enumerate().map(|(i, c)| format!("{c}({i})")).collect::<Vec<String>>().join(" ; "),
-        );
-        write!(f, "{s}")
+            seat_name(self.turn),
+            self.deck.len(), self.deck.len() / deal_size,
+            self.table.iter().enumerate()
+                .map(|(i, c)| format!("{}({i})", style.render(c, deck)))
+                .collect::<Vec<String>>().join(" ; "),
+        )
+    }
+}
+
+impl Display for Match {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(f, "{}", self.render(CardStyle::default(), DeckKind::default()))
     }
 }
 
 
-impl Turn {
-    fn toggle_turn(&mut self) {
+impl PlayerKind {
+    pub const ALL: [PlayerKind; MAX_SEATS] = [PlayerKind::Purple, PlayerKind::Green, PlayerKind::Orange,
+                                              PlayerKind::Blue, PlayerKind::Red, PlayerKind::Yellow];
+
+    /// Plain name, no colours
+    pub fn name(self) -> &'static str {
         match self {
-            Self::First => *self = Self::Shuffler,
-            Self::Shuffler => *self = Self::First,
+            PlayerKind::Purple => "Purple",
+            PlayerKind::Green  => "Green",
+            PlayerKind::Orange => "Orange",
+            PlayerKind::Blue   => "Blue",
+            PlayerKind::Red    => "Red",
+            PlayerKind::Yellow => "Yellow",
         }
     }
 }
 
-impl Display for PlayerKind {
-    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
-        match self {
-            PlayerKind::Purple => write!(f, "{}", purple_text()),
-            PlayerKind::Green  => write!(f, "{}", green_text()),
-        }
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnknownPlayer(pub String);
+
+impl FromStr for PlayerKind {
+    type Err = UnknownPlayer;
+
+    /// By name, in any case
+    fn from_str(s: &str) -> Result<PlayerKind, UnknownPlayer> {
+        PlayerKind::ALL.into_iter()
+            .find(|p| p.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| UnknownPlayer(s.to_string()))
     }
 }
 
-impl std::ops::Not for PlayerKind {
-    type Output = Self;
+impl Display for UnknownPlayer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let names: Vec<&str> = PlayerKind::ALL.iter().map(|p| p.name()).collect();
+        write!(f, "unknown player '{}', expected one of: {}", self.0, names.join(", "))
+    }
+}
 
-    fn not(self) -> Self {
+impl PlayerKind {
+    /// Coloured name, unless the style doesn't allow for colours
+    pub fn render(self, style: CardStyle) -> String {
         match self {
-            Self::Purple => Self::Green,
-            Self::Green  => Self::Purple,
+            PlayerKind::Purple => style.ansi("38;5;93", "Purple"),
+            PlayerKind::Green  => style.ansi("38;5;34", "Green"),
+            PlayerKind::Orange => style.ansi("38;5;208", "Orange"),
+            PlayerKind::Blue   => style.ansi("38;5;33", "Blue"),
+            PlayerKind::Red    => style.ansi("38;5;196", "Red"),
+            PlayerKind::Yellow => style.ansi("38;5;226", "Yellow"),
         }
     }
 }
 
+impl Display for PlayerKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(f, "{}", self.render(CardStyle::default()))
+    }
+}
+
 
 impl Display for PointTally {
+    /// Teams are called by their number, see `PointTally::render`
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
-        write!(f,
-               "=================
-First's scope:\t\t\t{},
-Shuf's scope:\t\t\t{},
-Nº cards:\t\t\t{},
-Nº denari\t\t\t{},
-Sette bello:\t\t\t{},
-Re bello:\t\t\t{},
-Napoli:  \t\t\t{},
-Primiera:\t\t\t{}
-=================",
-               self.scope_first, self.scope_shuf,
-               self.num_cards.map(|n| n.to_string()).unwrap_or("Nobody".to_string()),
-               self.num_denari.map(|n| n.to_string()).unwrap_or("Nobody".to_string()),
-               self.sette_bello,
-               self.re_bello,
-               self.napoli.map(|(t, n)| format!("{t} ({n})")).unwrap_or("Nobody".to_string()),
-               self.primiera.map(|n| n.to_string()).unwrap_or("Nobody".to_string()),
-        )
+        write!(f, "{}", self.render(&[]))
     }
 }
 
@@ -501,25 +975,398 @@ impl Default for Game {
     fn default() -> Self { Self::new() }
 }
 
+impl TurnBased for Game {
+    type Player = PlayerKind;
+    type Move = Play;
+    type Error = MoveError<'static>;
+
+    fn current_player(&self) -> PlayerKind {
+        self.color_playing()
+    }
+    fn legal_moves(&self) -> Vec<Play> {
+        if self.is_over() { vec![] } else { self.curr_match.legal_moves() }
+    }
+    /// Also wraps up each match: it gets scored and, unless that decided the game, the next
+    /// one is dealt
+    fn apply_move(&mut self, mov: &Play) -> Result<(), MoveError<'static>> {
+        self.play(mov)?;
+        if let Some(tally) = self.is_match_over() {
+            self.add_points(&tally);
+            if !self.is_over() {
+                self.rotate_dealer();
+                self.start_new_match();
+            }
+        } else {
+            self.next_turn();
+        }
+        Ok(())
+    }
+    /// A team wins together, it's named by the first of its players (see `Game::team_of`)
+    fn outcome(&self) -> Option<Outcome<PlayerKind>> {
+        let team = self.full_napoli().or_else(|| self.winning_team())?;
+        let first = self.rules().layout.members(team)[0];
+        Some(Outcome::Winner(self.players[first]))
+    }
+}
+
 
 pub fn purple_text() -> String {
-    format!("{0}[38;5;93mPurple{0}[0m", 27 as char)
+    PlayerKind::Purple.to_string()
 }
 pub fn green_text() -> String {
-    format!("{0}[38;5;34mGreen{0}[0m", 27 as char)
+    PlayerKind::Green.to_string()
+}
+
+impl Move {
+    /// As `make_move` reads it, see `Play::notation`
+    pub fn notation(&self) -> String {
+        self.play().notation()
+    }
+
+    /// The play it was, to be made again
+    pub fn play(&self) -> Play {
+        match &self.cards_taken {
+            None        => Play::Place(self.card_played),
+            Some(taken) => Play::Take(self.card_played, taken.clone()),
+        }
+    }
+
+    /// Says who made it by seat, see `Game::render_move` for their name
+    pub fn render(&self, style: CardStyle, deck: DeckKind) -> String {
+        self.describe(&seat_name(self.turn), style, deck)
+    }
+    fn describe(&self, who: &str, style: CardStyle, deck: DeckKind) -> String {
+        let print_card = |c: &Card| style.render(c, deck);
+        match &self.cards_taken {
+            None => format!("{} placed {} on the table",
+                            who,
+                            print_card(&self.card_played)),
+            Some(tables) => format!("{} took {} with {}",
+                                    who,
+                                    tables.iter().map(print_card).collect::<Vec<String>>().join("+"),
+                                    print_card(&self.card_played)),
+        }
+    }
+}
+
+impl Play {
+    /// In the notation `make_move` reads, by card name: `7d;3c+4b` or `t7d`
+    pub fn notation(&self) -> String {
+        let name = |c: &Card| c.notation(Notation::Italian);
+        match self {
+            Play::Place(card)       => format!("t{}", name(card)),
+            Play::Take(card, taken) => format!("{};{}", name(card), taken.iter().map(name).collect::<Vec<String>>().join("+")),
+        }
+    }
+
+    pub fn render(&self, style: CardStyle, deck: DeckKind) -> String {
+        match self {
+            Play::Place(card) => format!("place {}", style.render(card, deck)),
+            Play::Take(card, taken) => format!("take {} with {}",
+                                               taken.iter().map(|c| style.render(c, deck)).collect::<Vec<String>>().join("+"),
+                                               style.render(card, deck)),
+        }
+    }
 }
 
 impl Display for Move {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
-        match &self.cards_taken {
-            None => write!(f, "{} placed {} on the table",
-                           self.turn,
-                           self.card_played),
-            Some(tables) => write!(f, "{} took {} with {}",
-                                   self.turn,
-                                   tables.iter().map(|c| c.to_string()).collect::<Vec<String>>().join("+"),
-                                   self.card_played),
+        write!(f, "{}", self.render(CardStyle::default(), DeckKind::default()))
+    }
+}
+
+
+
+// ============ TESTS ================
+#[test]
+fn seeded_games_deal_the_same() {
+    let (mut a, mut b) = (Game::from_seed(1234), Game::from_seed(1234));
+    for _ in 0..3 {
+        assert_eq!(a.curr_match.players[0].curr_hand, b.curr_match.players[0].curr_hand);
+        assert_eq!(a.curr_match.players[1].curr_hand, b.curr_match.players[1].curr_hand);
+        assert_eq!(a.curr_match.table, b.curr_match.table);
+        assert_eq!(a.curr_match.deck, b.curr_match.deck);
+        a.start_new_match();
+        b.start_new_match();
+    }
+}
+
+#[test]
+fn random_games_only_make_legal_moves() {
+    use rand::seq::SliceRandom;
+    for seed in 0..10 {
+        let mut rng = seeded_rng(seed);
+        let mut game = Game::from_seed(seed);
+        let outcome = play_out(&mut game, 10_000, |_, moves| moves.choose(&mut rng).cloned()).unwrap();
+        assert!(outcome.is_some());
+        assert!(game.legal_moves().is_empty());
+    }
+}
+
+#[test]
+fn plays_are_validated() {
+    let mut m = dealt_match(ScopaRules::default(), &["7d", "Ac", "5s"], &["3c", "4b", "2d", "Rs"]);
+
+    assert!(matches!(m.play(&Play::Take(card("7d"), vec![card("3c"), card("3c")])), Err(MoveError::CardNotOnTable(_))));
+    assert!(matches!(m.play(&Play::Take(card("7d"), vec![card("4b"), card("2d")])), Err(MoveError::MismatchedValues)));
+    assert!(matches!(m.play(&Play::Take(card("Ac"), vec![card("Rs")])),             Err(MoveError::AceTakesEverything)));
+    assert!(matches!(m.play(&Play::Place(card("Rd"))),                               Err(MoveError::CardNotInHand(_))));
+    assert_eq!(m.table.len(), 4);
+
+    let legal = m.legal_moves();
+    assert!(legal.contains(&Play::Take(card("7d"), vec![card("3c"), card("4b")])));
+    assert!(legal.contains(&Play::Take(card("5s"), vec![card("3c"), card("2d")])));
+    assert!(legal.contains(&Play::Take(card("Ac"), m.table.iter().copied().collect())));
+    assert_eq!(legal.len(), 2 + 3); // Asso piglia tutto: the ace can't be placed
+
+    // The hand card goes to the pile once, however many cards it takes
+    let mov = m.make_move("0;0+1").unwrap().unwrap();
+    assert_eq!(mov.cards_taken, Some(vec![card("3c"), card("4b")]));
+    assert_eq!(m.players[0].pile.len(), 3);
+    assert_eq!(m.table.iter().copied().collect::<Vec<Card>>(), [card("2d"), card("Rs")]);
+}
+
+#[test]
+fn single_cards_must_be_taken_alone() {
+    let mut m = dealt_match(ScopaRules::default(), &["7d", "6c"], &["3c", "4b", "7s", "2d"]);
+
+    assert!(matches!(m.play(&Play::Take(card("7d"), vec![card("3c"), card("4b")])),
+                     Err(MoveError::MustTakeSingleCard(c)) if c == card("7s")));
+    assert!(matches!(m.make_move("0;0+1"), Err(MoveError::MustTakeSingleCard(_))));
+
+    let takes: Vec<Play> = m.legal_moves().into_iter().filter(|p| matches!(p, Play::Take(..))).collect();
+    assert_eq!(takes, [Play::Take(card("7d"), vec![card("7s")]),
+                       Play::Take(card("6c"), vec![card("4b"), card("2d")])]);
+
+    m.play(&Play::Take(card("7d"), vec![card("7s")])).unwrap();
+}
+
+#[test]
+fn rules_change_the_engine() {
+    let deal = |rules: ScopaRules| {
+        let mut m = dealt_match(rules, &["Ac"], &["3c", "Rd"]);
+        m.deck = Deck::default();
+        m.players[1].curr_hand = vec![];
+        m
+    };
+
+    // Placing an ace sweeps the table, which isn't a scopa by default
+    let mut m = deal(ScopaRules::default());
+    let mov = m.make_move("t0").unwrap().unwrap();
+    assert_eq!(mov.cards_taken.map(|c| c.len()), Some(2));
+    assert_eq!(m.players[0].scope, 0);
+
+    let mut m = deal(ScopaRules { ace_sweep_is_scopa: true, ..ScopaRules::default() });
+    m.make_move("0;").unwrap();
+    assert_eq!(m.players[0].scope, 1);
+
+    // Last card of the match
+    let mut m = deal(ScopaRules { ace_sweep_is_scopa: true, last_hand_scopa: false, ..ScopaRules::default() });
+    m.make_move("0;").unwrap();
+    assert_eq!(m.players[0].scope, 0);
+
+    // Without asso piglia tutto an ace is just a 1
+    let mut m = deal(ScopaRules { asso_piglia_tutto: false, ..ScopaRules::default() });
+    assert_eq!(m.legal_moves(), vec![Play::Place(card("Ac"))]);
+    assert!(matches!(m.make_move("0;0+1"), Err(MoveError::MismatchedValues)));
+    m.make_move("t0").unwrap();
+    assert_eq!(m.table.len(), 3);
+
+    // Re bello isn't scored nor shown
+    let tally = deal(ScopaRules { re_bello: false, napoli: false, ..ScopaRules::default() }).tally_final_points();
+    assert_eq!(tally.re_bello, None);
+    assert!(!tally.to_string().contains("Re bello"));
+    assert!(!tally.to_string().contains("Napoli"));
+
+    let mut game = Game::from_seed(0);
+    game.set_rules(ScopaRules { target_score: 11, ..ScopaRules::default() });
+    game.points[0] = 11;
+    assert_eq!(game.outcome(), Some(Outcome::Winner(PlayerKind::Purple)));
+    game.start_new_match();
+    assert_eq!(game.rules().target_score, 11);
+}
+
+#[test]
+fn primiera_takes_the_best_of_each_suit() {
+    let pile = |cs: &[&str]| Deck(cards(cs).into());
+
+    assert_eq!(primiera(&pile(&["7d", "7c", "7b", "7s"])), Some(84));
+    assert_eq!(primiera(&pile(&["7d", "6d", "Ac", "5b", "Rs", "2s"])), Some(21 + 16 + 15 + 12));
+    assert_eq!(primiera(&pile(&["7d", "7c", "7b", "6d"])), None); // No spade
+    assert_eq!(primiera(&pile(&[])), None);
+
+    // Three sevens and a missing suit used to win on the count of 7s
+    let mut m = Match::from_seed(0);
+    m.players[0].pile = pile(&["7d", "7c", "7b", "6d", "6c"]);
+    m.players[1].pile = pile(&["2d", "2c", "2b", "2s"]);
+    let tally = m.tally_final_points();
+    assert_eq!(tally.primiera, Some(1));
+    assert_eq!((tally.primiera_of(0), tally.primiera_of(1)), (None, Some(48)));
+    assert!(tally.to_string().contains("Team 1 (missing a suit - 48)"));
+}
+
+#[test]
+fn scopone_is_played_in_pairs() {
+    use rand::seq::SliceRandom;
+    let scopone = |layout| ScopaRules { layout, ..ScopaRules::default() };
+
+    let m = Match::new_with_rules(&mut seeded_rng(0), scopone(Layout::SCOPONE_SCIENTIFICO));
+    assert!(m.players.iter().all(|p| p.curr_hand.len() == 9));
+    assert_eq!((m.table.len(), m.deck.len()), (4, 0));
+    let m = Match::new_with_rules(&mut seeded_rng(0), scopone(Layout::SCOPONE_CLASSICO));
+    assert!(m.players.iter().all(|p| p.curr_hand.len() == 10));
+    assert_eq!((m.table.len(), m.deck.len()), (0, 0));
+
+    let mut game = Game::from_seed(3);
+    game.set_rules(scopone(Layout::SCOPONE_SCIENTIFICO));
+    let names = [PlayerKind::Purple, PlayerKind::Orange].map(|p| p.to_string()).join(" & ");
+    assert_eq!(game.team_name(0), names);
+
+    // Partners' piles are counted together
+    game.curr_match.players[0].pile = Deck(["7d"].map(|s| s.parse().unwrap()).into());
+    game.curr_match.players[2].pile = Deck(["Rd"].map(|s| s.parse().unwrap()).into());
+    assert_eq!(game.curr_match.team_pile(0).len(), 2);
+
+    // The deal goes around the table, seats change but partnerships don't
+    game.rotate_dealer();
+    assert_eq!(game.color_playing(), PlayerKind::Green);
+    assert_eq!(game.seat_of(PlayerKind::Purple), 3);
+    game.start_new_match();
+    assert_eq!(game.curr_match.teams, [1, 0, 1, 0]);
+
+    let mut rng = seeded_rng(3);
+    let outcome = play_out(&mut game, 10_000, |_, moves| moves.choose(&mut rng).cloned()).unwrap();
+    let Some(Outcome::Winner(winner)) = outcome else { panic!("The game is played to the end") };
+    assert_eq!(game.team_of(winner), game.winning_team().unwrap());
+    assert!(matches!(winner, PlayerKind::Purple | PlayerKind::Green)); // First of each team
+    assert!(game.points.iter().any(|&p| p >= 21));
+}
+
+#[test]
+fn any_number_of_seats() {
+    use rand::seq::SliceRandom;
+    let with = |layout, rotation| ScopaRules { layout, rotation, ..ScopaRules::default() };
+
+    assert_eq!(Layout::new(&[0, 1, 2], 3, 4), Ok(Layout::THREE_PLAYERS));
+    assert_eq!(Layout::new(&[0], 3, 4), Err(InvalidLayout::Seats(1)));
+    assert_eq!(Layout::new(&[0, 2, 0], 3, 4), Err(InvalidLayout::EmptyTeam(1)));
+    assert_eq!(Layout::new(&[0, 0], 3, 4), Err(InvalidLayout::OneTeam));
+    assert_eq!(Layout::SIX_PLAYERS.with_deal(6, 6), Err(InvalidLayout::TooManyCards(42)));
+
+    // Three players, each for themselves
+    let mut game = Game::from_seed(5);
+    game.set_rules(with(Layout::THREE_PLAYERS, Rotation::Around));
+    assert_eq!(game.curr_match.players.len(), 3);
+    assert_eq!(game.curr_match.deck.len(), 40 - 3 * 3 - 4);
+    let mut rng = seeded_rng(5);
+    let outcome = play_out(&mut game, 10_000, |_, moves| moves.choose(&mut rng).cloned()).unwrap();
+    assert!(matches!(outcome, Some(Outcome::Winner(PlayerKind::Purple | PlayerKind::Green | PlayerKind::Orange))));
+
+    // Six in three teams: the tally has what each seat and each team took
+    let mut game = Game::from_seed(6);
+    game.set_rules(with(Layout::SIX_PLAYERS, Rotation::LastTaker));
+    assert_eq!(game.points.len(), 3);
+    while !game.curr_match.is_over() {
+        let play = game.legal_moves()[0].clone();
+        game.play(&play).unwrap();
+        game.next_turn();
+    }
+    let last_taker = game.player_at(game.who_won_last_round);
+    let tally = game.is_match_over().unwrap();
+    assert_eq!((0..3).map(|t| tally.team(t).unwrap().cards).sum::<usize>(), 40);
+    for t in 0..3 {
+        let [a, b] = tally.members(t)[..] else { panic!("teams of two") };
+        let (seat_a, seat_b) = (tally.seat(a).unwrap(), tally.seat(b).unwrap());
+        assert_eq!(seat_a.scope + seat_b.scope, tally.team(t).unwrap().scope);
+        assert_eq!(seat_a.cards + seat_b.cards, tally.team(t).unwrap().cards);
+    }
+    game.rotate_dealer();
+    game.start_new_match();
+    assert_eq!(game.color_playing(), last_taker);
+
+    // Whoever played first keeps doing so
+    let mut game = Game::from_seed(6);
+    game.set_rules(with(Layout::THREE_PLAYERS, Rotation::Fixed));
+    game.rotate_dealer();
+    game.start_new_match();
+    assert_eq!(game.color_playing(), PlayerKind::Purple);
+}
+
+#[test]
+fn capture_modes() {
+    // Scopa a quindici: only what makes 15, never by value
+    let mut m = dealt_match(ScopaRules::quindici(), &["Rd", "5c"], &["5s", "3b", "2c"]);
+    let takes: Vec<Play> = m.legal_moves().into_iter().filter(|p| matches!(p, Play::Take(..))).collect();
+    assert_eq!(takes, [Play::Take(card("Rd"), vec![card("3b"), card("2c")]),
+                       Play::Take(card("Rd"), vec![card("5s")]),
+                       Play::Take(card("5c"), vec![card("5s"), card("3b"), card("2c")])]);
+    assert!(matches!(m.play(&Play::Take(card("5c"), vec![card("5s")])), Err(MoveError::MismatchedValues)));
+    assert!(matches!(m.make_move("0;1+2"), Ok(Some(_))));
+
+    // Scopa d'assi: the ace sweeps, unless there's an ace to take
+    let mut m = dealt_match(ScopaRules::assi(), &["Ac", "4c"], &["5s", "3b", "2c"]);
+    assert!(m.legal_moves().contains(&Play::Take(card("Ac"), m.table.iter().copied().collect())));
+    m.table.push_to_top(card("As"));
+    assert!(m.legal_moves().contains(&Play::Take(card("Ac"), vec![card("As")])));
+    assert!(m.legal_moves().contains(&Play::Place(card("Ac"))));
+    assert!(matches!(m.make_move("0;0+1+2+3"), Err(MoveError::MismatchedValues)));
+    m.make_move("0;3").unwrap();
+    assert_eq!(m.table.len(), 3);
+
+    use rand::seq::SliceRandom;
+    for rules in [ScopaRules::quindici(), ScopaRules::assi()] {
+        let mut rng = seeded_rng(1);
+        let mut game = Game::with_rules(1, rules);
+        assert!(play_out(&mut game, 10_000, |_, moves| moves.choose(&mut rng).cloned()).unwrap().is_some());
+    }
+}
+
+#[test]
+fn legal_moves_can_be_made_as_they_are() {
+    for rules in [ScopaRules::default(), ScopaRules::cirulla(), ScopaRules::quindici(), ScopaRules::assi()] {
+        let mut game = Game::with_rules(9, rules);
+        while !game.curr_match.is_over() {
+            let moves = game.curr_match.legal_moves();
+            for mov in &moves {
+                assert!(game.curr_match.clone().make_move(mov).is_ok(), "{mov:?} was offered but isn't legal");
+            }
+            game.make_move(moves[moves.len() / 2].clone()).unwrap();
+            game.next_turn();
         }
     }
+
+    // Presa obbligatoria: cards that can take can't be placed
+    let mut m = dealt_match(ScopaRules { must_take: true, ..ScopaRules::default() }, &["7d", "Rc"], &["3c", "4b"]);
+    assert_eq!(m.legal_moves(), [Play::Take(card("7d"), vec![card("3c"), card("4b")]), Play::Place(card("Rc"))]);
+    assert!(matches!(m.make_move("t0"), Err(MoveError::MustTake(_))));
+    m.make_move(Play::Place(card("Rc"))).unwrap();
 }
 
+#[test]
+fn moves_by_card_name() {
+    let deal = || dealt_match(ScopaRules::default(), &["7d", "Rs", "5b"], &["3c", "4b", "Rd", "2c"]);
+
+    let mut m = deal();
+    let mov = m.make_move("7d;3c+4b").unwrap().unwrap();
+    assert_eq!(mov.cards_taken, Some(vec![card("3c"), card("4b")]));
+    let mov = deal().make_move("Rs;Rd").unwrap().unwrap();
+    assert_eq!(mov.cards_taken, Some(vec![card("Rd")]));
+    let mov = deal().make_move("7d;0+4b").unwrap().unwrap(); // Names and positions can be mixed
+    assert_eq!(mov.card_played, card("7d"));
+    assert_eq!(deal().make_move("t5b").unwrap().unwrap().cards_taken, None);
+
+    // Just the card, when there's only one thing it can do
+    assert_eq!(deal().make_move("Rs").unwrap().unwrap().cards_taken, Some(vec![card("Rd")]));
+    assert_eq!(deal().make_move("7d").unwrap().unwrap().cards_taken, Some(vec![card("3c"), card("4b")]));
+    let mut m = deal();
+    m.table.push_to_top(card("5s"));
+    let err = m.make_move("7d").unwrap_err();
+    assert!(matches!(&err, MoveError::Ambiguous(plays) if plays.len() == 2));
+    assert_eq!(err.to_string(), "that could be any of 7d;2c+5s, 7d;3c+4b, say which");
+
+    // Cards that aren't where they're said to be
+    assert!(matches!(deal().make_move("Ad;"), Err(MoveError::CardNotInHand(c)) if c == card("Ad")));
+    assert!(matches!(deal().make_move("7d;3c+4s"), Err(MoveError::CardNotOnTable(c)) if c == card("4s")));
+    assert!(matches!(deal().make_move("7x"), Err(MoveError::ParseError(_))));
+    assert!(matches!(deal().make_move("0;0+1 and more"), Err(MoveError::ParseError(_))));
+}
@@ -1,11 +1,95 @@
+use cards_core::{arg_parsed, arg_value, usage_error, CardStyle, DeckKind};
 use scopa::*;
-use core::*;
 use std::io::stdin;
 use std::io::Write;
+use std::net::TcpListener;
 
 fn main() {
     let mut input = String::new();
-    let mut game = Game::new();
+    // `--seed N` replays the exact same sequence of deals
+    let mut game = match arg_parsed("--seed") {
+        Some(seed) => Game::from_seed(seed),
+        None       => Game::new(),
+    };
+    let mut rules = match arg_value("--mode").as_deref() {
+        Some("cirulla")  => ScopaRules { layout: game.rules().layout, ..ScopaRules::cirulla() },
+        Some("quindici") => ScopaRules { layout: game.rules().layout, ..ScopaRules::quindici() },
+        Some("assi")     => ScopaRules { layout: game.rules().layout, ..ScopaRules::assi() },
+        Some("scopa") | None => *game.rules(),
+        Some(mode) => usage_error(format!("unknown mode '{mode}', expected one of: scopa, cirulla, quindici, assi")),
+    };
+    if let Some(variant) = arg_parsed("--variant") {
+        rules.layout = variant;
+    }
+    // `--teams 0,1,2,0,1,2`: one seat per number, saying what team it's on
+    if let Some(teams) = arg_value("--teams") {
+        let teams: Vec<Team> = teams.split(',')
+            .map(|t| t.trim().parse().unwrap_or_else(|_| usage_error("teams must be comma separated numbers")))
+            .collect();
+        rules.layout = Layout::new(&teams, rules.layout.hand_size, rules.layout.table_size).unwrap_or_else(|e| usage_error(e));
+    }
+    let hand_size = arg_parsed("--hand").unwrap_or(rules.layout.hand_size);
+    let table_size = arg_parsed("--table").unwrap_or(rules.layout.table_size);
+    rules.layout = rules.layout.with_deal(hand_size, table_size).unwrap_or_else(|e| usage_error(e));
+    if let Some(rotation) = arg_parsed("--rotation") {
+        rules.rotation = rotation;
+    }
+    if let Some(undo) = arg_parsed("--undo-seen-moves") {
+        rules.undo_seen_moves = undo;
+    }
+    game.set_rules(rules);
+    if let Some(style) = arg_parsed("--style") {
+        game.style = style;
+    }
+    if let Some(deck) = arg_parsed("--deck") {
+        if !DECKS.contains(&deck) { usage_error("scopa is played with a 40-card deck") }
+        game.deck_kind = deck;
+    }
+    // `--replay game.txt` steps through a game written down with `--record`
+    if let Some(path) = arg_value("--replay") {
+        let text = std::fs::read_to_string(&path).unwrap_or_else(|e| usage_error(format!("can't read {path}: {e}")));
+        let record: GameRecord = text.parse().unwrap_or_else(|e| usage_error(format!("{path}, {e}")));
+        replay(&record, game.style, game.deck_kind);
+        return;
+    }
+    // `--serve 0.0.0.0:7777` hosts the game for players to join with `--connect`, see `scopa::net`
+    if let Some(addr) = arg_value("--serve") {
+        let listener = TcpListener::bind(&addr).unwrap_or_else(|e| usage_error(format!("can't listen on {addr}: {e}")));
+        let names: Vec<&str> = game.players.iter().map(|p| p.name()).collect();
+        println!("Hosting scopa on {} (seed '{}') for {}", listener.local_addr().expect("Listening on an address"), game.seed, names.join(", "));
+        if let Err(e) = Server::new(game).run(listener) {
+            eprintln!("error: can't take in any more players: {e}");
+            std::process::exit(1);
+        }
+        return;
+    }
+    // `--connect host:7777 --as Green [--token N]`, the token to take the seat back after leaving
+    if let Some(addr) = arg_value("--connect") {
+        let player = arg_parsed("--as").unwrap_or_else(|| usage_error("say who you're playing as, e.g. `--as Green`"));
+        let token = arg_parsed("--token");
+        play_remote(&addr, player, token, game.style, game.deck_kind);
+        return;
+    }
+    // `--players human,mc`: who plays each colour, in order. Anyone not listed is human
+    let players = arg_value("--players").unwrap_or_default();
+    let players: Vec<&str> = players.split(',').filter(|p| !p.is_empty()).collect();
+    let mut bots: Vec<Option<Bot>> = (0..game.players.len())
+        .map(|i| match players.get(i).copied().unwrap_or("human") {
+            "human" => None,
+            bot     => Some(Bot::new(bot.parse().unwrap_or_else(|e| usage_error(e)), game.seed.wrapping_add(i as u64))),
+        })
+        .collect();
+    // `--record game.txt`: written down after every move, to be watched again with `--replay`
+    let record_to = arg_value("--record");
+    let warned = std::cell::Cell::new(false); // Once is enough, the game goes on anyway
+    let save = |game: &Game| if let Some(path) = &record_to {
+        if let Err(e) = std::fs::write(path, game.history.to_string()) {
+            if !warned.replace(true) { eprintln!("warning: can't write the record to {path}: {e}") }
+        }
+    };
+    let score = |game: &Game| game.team_names().iter().zip(&game.points)
+        .map(|(name, p)| format!("{name} '{p}'"))
+        .collect::<Vec<String>>().join(" - ");
 
     println!(
 r#"Welcome to...
@@ -17,59 +101,131 @@ r#"Welcome to...
               |_|
 
 The best game ever made, wooo
-
+(Seed is '{0}', pass `--seed {0}` to replay these deals)
 
 Press the Any button to begin...
-"#);
+"#, game.seed);
 
     stdin().read_line(&mut input).expect("Could not read from stdin");
     clear_term();
+    // With several people on the same device, whose turn it is has to be handed over: the screen
+    // is blanked and their hand only shown once they say they're ready
+    let humans = bots.iter().filter(|b| b.is_none()).count();
+    let delay = std::time::Duration::from_millis(arg_parsed("--delay").unwrap_or(1500));
+    let mut last_human: Option<usize> = None;
+
+    // Everything public that has happened, and how much of it each player has been shown
+    let mut log: Vec<String> = vec![];
+    let mut shown = vec![0; game.players.len()];
+    let mut events_logged = 0;
+    let mut notice: Option<String> = None;
     loop {
+        // Declarations and such, as they're made
+        log.extend(game.curr_match.events[events_logged..].iter().map(|e| game.render_event(e)));
+        events_logged = game.curr_match.events.len();
+
+        let player = game.color_playing();
+        let i = game.players.iter().position(|&p| p == player).expect("Whoever's playing is at the table");
+
+        let made = if let Some(bot) = bots[i].as_mut() {
+            println!("{} ({}) is thinking...", player.render(game.style), bot.strength);
+            game.curr_match.mark_seen();
+            let play = bot.choose(&game.view(player), &game.curr_match.legal_moves()).expect("There's always a move to make");
+            game.make_move(play)
+        } else {
+            if humans > 1 && last_human != Some(i) {
+                clear_term();
+                std::thread::sleep(delay);
+                let hint = if last_human.is_some() { " (or type `undo` to take your move back)" } else { "" };
+                print!("Pass the device to {0}{hint}. Press enter when {0} is ready...", player.render(game.style));
+                std::io::stdout().flush().expect("could not flush stdout");
+                input.clear();
+                if stdin().read_line(&mut input).expect("Could not read from stdin") == 0 { break } // EOF
+                if input.trim() == "undo" {
+                    notice = undo_human_move(&mut game, &bots, &mut log).err().map(|e| format!("undo error: {e}"));
+                    events_logged = events_logged.min(game.curr_match.events.len());
+                    continue;
+                }
+            }
+            last_human = Some(i);
+            clear_term();
+            // Moves made by anyone else, they've now seen
+            if game.curr_match.moves.last().is_some_and(|m| game.player_at(m.turn) != player) {
+                game.curr_match.mark_seen();
+            }
 
-        println!("Current player is: '{}'", game.color_playing());
-        println!("Score is: {} '{}' - '{}' {}", purple_text(), game.purple_points, game.green_points, green_text());
-        println!("{}", game.curr_match);
-        print!("You current cards are: ");
-        game.print_cards_of_curr_player();
+            if shown[i] < log.len() {
+                println!("Since your last turn:");
+                for line in &log[shown[i]..] { println!("  {line}") }
+                println!();
+                shown[i] = log.len();
+            }
+            if let Some(notice) = notice.take() { println!("{notice}\n") }
+
+            println!("Current player is: '{}'", player.render(game.style));
+            println!("Score is: {}", score(&game));
+            println!("{}", game.curr_match.render(game.style, game.deck_kind));
+            print!("You current cards are: ");
+            game.print_cards_of_curr_player();
 
 
-        println!("Waiting for input now....");
-        print!(">"); std::io::stdout().flush().expect("could not flush stdout");
+            println!("Waiting for input now....");
+            print!(">"); std::io::stdout().flush().expect("could not flush stdout");
 
-        stdin().read_line(&mut input).expect("Could not read from stdin");
-        input = input.trim().to_string();
+            input.clear();
+            if stdin().read_line(&mut input).expect("Could not read from stdin") == 0 { break } // EOF
+            input = input.trim().to_string();
+
+            if input == "?" {
+                let moves = game.curr_match.legal_moves();
+                notice = Some(format!("You can: {}", moves.iter().map(|m| m.render(game.style, game.deck_kind)).collect::<Vec<String>>().join(", ")));
+                continue;
+            }
+            if input == "undo" {
+                notice = undo_human_move(&mut game, &bots, &mut log).err().map(|e| format!("undo error: {e}"));
+                events_logged = events_logged.min(game.curr_match.events.len());
+                continue;
+            }
+            if input == "redo" {
+                match game.redo() {
+                    Ok(mov) => Ok(Some(mov)),
+                    Err(e) => {
+                        notice = Some(format!("redo error: {e}"));
+                        continue;
+                    },
+                }
+            } else {
+                game.make_move(&input)
+            }
+        };
 
-        let move_made = match game.make_move(&input) {
+        let move_made = match made {
             Ok(mov) => mov,
             Err(e) => {
-                clear_term();
-                println!("move error: {e:?}");
+                notice = Some(format!("move error: {e}"));
                 continue;
             },
         };
+        if let Some(m) = move_made {
+            log.push(game.render_move(&m));
+            if bots[i].is_none() { shown[i] = log.len() } // They know what they just did
+        }
+        save(&game);
 
         if let Some(tally) = game.is_match_over() {
             clear_term();
-            let (purp_p, gren_p) = match game.who_is_first {
-                PlayerKind::Purple => (tally.first_points(), tally.shuf_points()),
-                PlayerKind::Green  => (tally.shuf_points(), tally.first_points()),
-            };
-            println!("Match over: Purple got '{purp_p}' points, Green got '{gren_p}'");
+            let made = game.add_points(&tally);
+            save(&game);
+            println!("Match over: {}", game.team_names().iter().zip(&made)
+                     .map(|(name, p)| format!("{name} got '{p}' points"))
+                     .collect::<Vec<String>>().join(", "));
             println!();
-            println!("The breakdown is:\n{}\n", tally);
-            game.purple_points += purp_p;
-            game.green_points  += gren_p;
-            println!("Updated running score is: {} '{}' - '{}' {}\n",
-                     purple_text(), game.purple_points,
-                     game.green_points, green_text());
+            println!("The breakdown is:\n{}\n", tally.render(&game.team_names()));
+            println!("Updated running score is: {}\n", score(&game));
 
             // Full napoli takes preference over normal winner
-            if has_full_napoli(&game.curr_match.player_first.pile) {
-                println!("{} has achieved a full napoli: they win. What a nerd lmfao", game.who_is_first);
-                break;
-            }
-            else if has_full_napoli(&game.curr_match.player_shuffler.pile) {
-                println!("{} has achieved a full napoli: they win. What a nerd lmfao", !game.who_is_first);
+            if let Some(napoli) = game.full_napoli() {
+                println!("{} has achieved a full napoli: they win. What a nerd lmfao", game.team_name(napoli));
                 break;
             }
             else if let Some((player_name, win_p, lose_p)) = game.winner() {
@@ -78,27 +234,134 @@ Press the Any button to begin...
             }
 
             println!("Restarting match....");
-            game.toggle_whose_first();
-            game.curr_match = Match::new();
+            game.rotate_dealer();
+            game.start_new_match();
+            events_logged = 0;
+            log.clear();
+            shown.iter_mut().for_each(|s| *s = 0);
+            last_human = None; // Everyone has new cards
 
             println!("Press any button to start the next match...");
             stdin().read_line(&mut input).expect("Could not read from stdin");
-
-            continue;
         } else {
-            use std::{thread, time};
+            game.next_turn();
+        }
+    }
+}
 
-            clear_term();
+/// Take back the last move made by a human, and whatever the bots played after it. Nothing is
+/// taken back if that move can't be. What was taken back goes in the log
+fn undo_human_move(game: &mut Game, bots: &[Option<Bot>], log: &mut Vec<String>) -> Result<(), UndoError> {
+    let is_human = |game: &Game, m: &Move| {
+        let player = game.player_at(m.turn);
+        bots[game.players.iter().position(|&p| p == player).expect("Whoever moved is at the table")].is_none()
+    };
+    let count = game.curr_match.moves.iter().rev()
+        .position(|m| is_human(game, m))
+        .ok_or(UndoError::NothingToUndo)? + 1;
+    if game.curr_match.undoable() < count { return Err(UndoError::Seen); }
+
+    for _ in 0..count {
+        let undone = game.undo()?;
+        log.push(format!("Taken back: {}", game.render_move(&undone)));
+    }
+    Ok(())
+}
+
+/// Step through `record` a move at a time: enter (or `n`) goes forwards, `p` backwards, a number
+/// jumps to that step and `q` quits
+fn replay(record: &GameRecord, style: CardStyle, deck: DeckKind) {
+    let frames = record.frames().unwrap_or_else(|e| usage_error(e));
+    let last = frames.len() - 1;
+    let mut i = 0;
+    let mut input = String::new();
+    loop {
+        let Frame { game, tally } = &frames[i];
+        let mut game = game.clone();
+        (game.style, game.deck_kind) = (style, deck);
+        let score = game.team_names().iter().zip(&game.points)
+            .map(|(name, p)| format!("{name} '{p}'"))
+            .collect::<Vec<String>>().join(" - ");
 
-            println!("Waiting 1.5 seconds before switching...");
-            //thread::sleep(time::Duration::from_millis(1500));
-            game.toggle_turn();
-        }
         clear_term();
-        println!("The last player just played: '{}'\n\n", move_made.map(|m| m.to_string()).unwrap_or("No previous move".to_string()));
+        println!("Replaying seed '{}', step {i} of {last}", record.seed);
+        println!("Score is: {score}");
+        for event in &game.curr_match.events { println!("{}", game.render_event(event)) }
+        // Only this match's, at its start there's nothing yet
+        if let Some(mov) = game.history.matches.last().and_then(|m| m.moves.last()) {
+            println!("{}", game.render_move(mov));
+        }
+        println!("{}", game.curr_match.render(style, deck));
+        for (seat, player) in game.curr_match.players.iter().enumerate() {
+            let hand: Vec<String> = player.curr_hand.iter().map(|c| style.render(c, deck)).collect();
+            println!("{} holds: {}", game.player_at(seat).render(style), hand.join(" "));
+        }
+        if let Some(tally) = tally {
+            println!("\nMatch over, the breakdown is:\n{}", tally.render(&game.team_names()));
+        }
+        if let (true, Some((name, win_p, lose_p))) = (i == last, game.winner()) {
+            println!("\n{name} won with {win_p} points to {lose_p}");
+        }
+
+        print!("\n[enter] next, [p] previous, [N] go to step N, [q] quit >");
+        std::io::stdout().flush().expect("could not flush stdout");
+        input.clear();
+        if stdin().read_line(&mut input).expect("Could not read from stdin") == 0 { break } // EOF
+        match input.trim() {
+            "" | "n" => i = (i + 1).min(last),
+            "p"      => i = i.saturating_sub(1),
+            "q"      => break,
+            step     => if let Ok(step) = step.parse::<usize>() { i = step.min(last) },
+        }
+    }
+}
+
+/// Play a game hosted somewhere else: what the server says is printed as it comes, every line
+/// typed is sent as a move (an empty one asks for the state again)
+fn play_remote(addr: &str, player: PlayerKind, token: Option<u64>, style: CardStyle, deck: DeckKind) {
+    let mut conn = Connection::connect(addr).unwrap_or_else(|e| usage_error(format!("can't connect to {addr}: {e}")));
+    conn.send(&Message::Hello { player, token }).unwrap_or_else(|e| usage_error(format!("can't talk to {addr}: {e}")));
+
+    let mut writer = conn.writer().expect("Could not talk to the server");
+    std::thread::spawn(move || {
+        for line in stdin().lines() {
+            let Ok(line) = line else { break };
+            let msg = match line.trim() {
+                ""  => Message::State,
+                mov => Message::Move(mov.to_string()),
+            };
+            if writeln!(writer, "{msg}").is_err() { break }
+        }
+    });
+
+    // Shown above the next view, which clears the screen
+    let mut recent: Vec<String> = vec![];
+    loop {
+        match conn.recv() {
+            Ok(Some(Message::Welcome { player, token })) =>
+                recent.push(format!("You're {}. Pass `--token {token}` to come back if you get disconnected", player.render(style))),
+            Ok(Some(Message::View(view))) => {
+                clear_term();
+                for line in recent.drain(..) { println!("{line}") }
+                println!("{}", view.render(style, deck));
+                if view.turn == view.seat { print!("Your turn >") } else { print!("Waiting for {}...", view.players[view.turn].render(style)) }
+                std::io::stdout().flush().expect("could not flush stdout");
+            },
+            Ok(Some(Message::Moved { player, mov })) => recent.push(format!("{} played {mov}", player.render(style))),
+            Ok(Some(Message::Event(text))) => recent.push(text),
+            Ok(Some(Message::Score(score))) => recent.push(format!("Match over, the score is {}", score.iter().map(|p| p.to_string()).collect::<Vec<String>>().join(" - "))),
+            Ok(Some(Message::Over(text))) => recent.push(format!("Game over: {text}")),
+            Ok(Some(Message::Error(e))) => { print!("error: {e}\n>"); std::io::stdout().flush().expect("could not flush stdout") },
+            Ok(Some(_)) => {},
+            Ok(None) | Err(_) => break,
+        }
     }
+    for line in recent { println!("{line}") }
+    println!("\nThe server hung up");
 }
 
+/// Screen and scrollback both, so the last hand can't be scrolled back to
 fn clear_term() {
-    print!("{}[2J", 27 as char);
+    print!("\x1B[2J\x1B[3J\x1B[H");
+    std::io::stdout().flush().expect("could not flush stdout");
 }
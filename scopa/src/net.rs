@@ -0,0 +1,517 @@
+//! Scopa over TCP. The server hosts the `Game`, every player connects to it with a client of
+//! their own and is only ever sent what their `PlayerView` has in it.
+//!
+//! # Protocol
+//! Plain text, one message per line, words separated by spaces. Cards are written in the
+//! Italian notation (`7d`, `Rs`...), lists of cards or numbers are comma separated, `-` when
+//! empty.
+//!
+//! Client to server:
+//! - `HELLO <player> [<token>]`: sit down as `<player>` (`Purple`, `Green`...). The token is only
+//!   needed to take the seat back after a disconnection
+//! - `MOVE <move>`: any move `Match::make_move` reads, e.g. `MOVE 7d;3c+4b` or `MOVE t0`
+//! - `STATE`: send the `VIEW` again
+//!
+//! Server to client:
+//! - `WELCOME <player> <token>`: the seat is yours, keep the token to reconnect
+//! - `VIEW seat=<n> turn=<n> players=<p,...> hand=<cards> table=<cards> hands=<n,...> deck=<n>
+//!   scope=<n,...> captured=<n,...> score=<n,...>`: what this seat may know, sent on arrival and
+//!   after every move. `players` says who sits at each seat, `score` is each team's
+//! - `MOVED <player> <move>`: a move was made, by card name
+//! - `EVENT <text>`: something else happened in the match, e.g. a hand declared in cirulla. Sent
+//!   as it happens and, to whoever sits down, every one of the match so far
+//! - `SCORE <n,...>`: a match ended, this is the running score of each team
+//! - `OVER <text>`: the game is over, and who won it
+//! - `ERROR <text>`: the last message was refused: an illegal move, a move out of turn...
+
+use std::fmt::{Display, Formatter};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use cards_core::*;
+
+use crate::*;
+
+/// How long a write to a client can stall before they're taken as gone. Writes are made with the
+/// game locked, a client that stops reading can't hold everyone else up for longer than this
+const WRITE_TIMEOUT: Duration = Duration::from_secs(2);
+/// Longest line a client may send, in bytes. Whoever sends a longer one is disconnected
+const MAX_LINE: u64 = 4096;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Message {
+    Hello { player: PlayerKind, token: Option<u64> },
+    Move(String),
+    State,
+    Welcome { player: PlayerKind, token: u64 },
+    View(RemoteView),
+    Moved { player: PlayerKind, mov: String },
+    Event(String),
+    Score(Vec<usize>),
+    Over(String),
+    Error(String),
+}
+
+/// What a seat is sent of the game, see `VIEW` in the protocol
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RemoteView {
+    pub seat: Seat,
+    pub turn: Seat,
+    /// Who sits at each seat
+    pub players: Vec<PlayerKind>,
+    pub hand: Vec<Card>,
+    pub table: Vec<Card>,
+    /// How many cards each seat is holding
+    pub hands: Vec<usize>,
+    pub deck: usize,
+    pub scope: Vec<usize>,
+    pub captured: Vec<usize>,
+    /// Of each team
+    pub score: Vec<usize>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseMessageError(pub String);
+
+/// Hosts a game for clients to connect to. Clones share the same game
+#[derive(Clone, Debug)]
+pub struct Server {
+    shared: Arc<Mutex<Shared>>,
+}
+
+#[derive(Debug)]
+struct Shared {
+    game: Game,
+    /// One per entry of `game.players`
+    seats: Vec<RemoteSeat>,
+    connections: u64,
+    /// How many of `game.curr_match.events` have been broadcast
+    events_sent: usize,
+}
+
+#[derive(Debug, Default)]
+struct RemoteSeat {
+    /// `None` until someone sits down
+    token: Option<u64>,
+    /// Id and stream of whoever is connected to it right now
+    stream: Option<(u64, TcpStream)>,
+}
+
+/// One end of a connection to a server, see the protocol above
+#[derive(Debug)]
+pub struct Connection {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Server {
+    pub fn new(game: Game) -> Server {
+        let seats = game.players.iter().map(|_| RemoteSeat::default()).collect();
+        let events_sent = game.curr_match.events.len();
+        Server { shared: Arc::new(Mutex::new(Shared { game, seats, connections: 0, events_sent })) }
+    }
+
+    /// Take in clients from `listener`, each in a thread of its own. Only returns if the
+    /// listener fails
+    pub fn run(&self, listener: TcpListener) -> io::Result<()> {
+        for stream in listener.incoming() {
+            let server = self.clone();
+            let stream = stream?;
+            thread::spawn(move || server.serve(stream));
+        }
+        Ok(())
+    }
+
+    /// The game as it stands
+    pub fn game(&self) -> Game {
+        self.shared.lock().expect("A client thread panicked").game.clone()
+    }
+
+    /// Talk to a single client until it hangs up or fails. Its seat stays taken for it to come
+    /// back to
+    fn serve(&self, stream: TcpStream) -> io::Result<()> {
+        let id = {
+            let mut shared = self.shared.lock().expect("A client thread panicked");
+            shared.connections += 1;
+            shared.connections
+        };
+        let mut seat: Option<usize> = None; // Index in `game.players`
+        let result = self.talk(stream, id, &mut seat);
+
+        if let Some(i) = seat {
+            let mut shared = self.shared.lock().expect("A client thread panicked");
+            if shared.seats[i].stream.as_ref().is_some_and(|(s, _)| *s == id) { shared.seats[i].stream = None }
+        }
+        result
+    }
+
+    fn talk(&self, stream: TcpStream, id: u64, seat: &mut Option<usize>) -> io::Result<()> {
+        stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut writer = stream.try_clone()?;
+        let mut line = String::new();
+
+        loop {
+            line.clear();
+            if (&mut reader).take(MAX_LINE).read_line(&mut line)? == 0 { return Ok(()); }
+            if !line.ends_with('\n') && line.len() as u64 == MAX_LINE {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "the client sent too long a line"));
+            }
+
+            let mut shared = self.shared.lock().expect("A client thread panicked");
+            let result = match (line.trim_end().parse::<Message>(), *seat) {
+                (Err(e), _) => Err(e.to_string()),
+                (Ok(Message::Hello { player, .. }), Some(i)) if shared.game.players[i] != player =>
+                    Err(format!("you're already sitting as {}", shared.game.players[i].name())),
+                (Ok(Message::Hello { player, token }), _) => shared.sit(player, token, (id, stream.try_clone()?))
+                                                                  .map(|i| *seat = Some(i)),
+                (Ok(_), None) => Err("say HELLO first".to_string()),
+                (Ok(Message::Move(mov)), Some(i)) => shared.make_move(i, &mov),
+                (Ok(Message::State), Some(i)) => { shared.send_view(i); Ok(()) },
+                (Ok(other), Some(_)) => Err(format!("clients don't send {}", other.keyword())),
+            };
+            if let Err(e) = result { writeln!(writer, "{}", Message::Error(e))? }
+        }
+    }
+}
+
+impl Shared {
+    /// Seat whoever is on `stream` as `player`, returns the index of their seat
+    fn sit(&mut self, player: PlayerKind, token: Option<u64>, stream: (u64, TcpStream)) -> Result<usize, String> {
+        let i = self.game.players.iter().position(|&p| p == player)
+            .ok_or_else(|| format!("{} isn't playing", player.name()))?;
+        let token = match (self.seats[i].token, token) {
+            (None, _)                              => rand::random(),
+            (Some(taken), Some(t)) if taken == t   => t, // Welcome back
+            (Some(_), _)                           => return Err(format!("{} is already taken", player.name())),
+        };
+        self.seats[i] = RemoteSeat { token: Some(token), stream: Some(stream) };
+        self.send(i, &Message::Welcome { player, token });
+        for text in self.game.curr_match.events.iter().map(|e| self.event_text(e)).collect::<Vec<String>>() {
+            self.send(i, &Message::Event(text));
+        }
+        self.send_view(i);
+        Ok(i)
+    }
+
+    fn make_move(&mut self, i: usize, mov: &str) -> Result<(), String> {
+        let player = self.game.players[i];
+        if self.game.is_over() { return Err("the game is over".to_string()); }
+        if self.game.color_playing() != player { return Err(format!("it's {}'s turn", self.game.color_playing().name())); }
+
+        let play = mov.into_play(&self.game.curr_match).map_err(|e| e.to_string())?;
+        let points = self.game.points.clone();
+        let matches = self.game.history.matches.len();
+        self.game.apply_move(&play).map_err(|e| e.to_string())?;
+        if self.game.history.matches.len() != matches { self.events_sent = 0 }
+
+        let made = self.game.last_move.as_ref().expect("A move was just made").notation();
+        self.broadcast(&Message::Moved { player, mov: made });
+        if self.game.points != points {
+            self.broadcast(&Message::Score(self.game.points.clone()));
+        }
+        if let Some(outcome) = self.game.outcome() {
+            let winner = match outcome {
+                Outcome::Winner(p) => self.game.rules().layout.members(self.game.team_of(p)).iter()
+                    .map(|&i| self.game.players[i].name())
+                    .collect::<Vec<&str>>().join(" & "),
+                Outcome::Draw      => "nobody".to_string(),
+            };
+            self.broadcast(&Message::Over(format!("{winner} won")));
+        }
+        let events = &self.game.curr_match.events[self.events_sent..];
+        for text in events.iter().map(|e| self.event_text(e)).collect::<Vec<String>>() {
+            self.broadcast(&Message::Event(text));
+        }
+        self.events_sent = self.game.curr_match.events.len();
+        for i in 0..self.seats.len() { self.send_view(i) }
+        Ok(())
+    }
+
+    fn send_view(&mut self, i: usize) {
+        let view = self.game.view(self.game.players[i]);
+        let players = (0..self.game.players.len()).map(|seat| self.game.player_at(seat)).collect();
+        self.send(i, &Message::View(RemoteView {
+            seat: view.seat.expect("Players have a seat"),
+            turn: view.turn,
+            players,
+            hand: view.hand,
+            table: view.table,
+            hands: view.hand_sizes,
+            deck: view.deck_left,
+            scope: view.scope,
+            captured: view.captured,
+            score: self.game.points.clone(),
+        }));
+    }
+
+    /// `event` as sent over the wire, by player and in the Italian notation
+    fn event_text(&self, event: &Event) -> String {
+        event.describe(self.game.player_at(event.seat()).name(), CardStyle::Ascii, self.game.deck_kind)
+    }
+
+    /// To whoever is connected to seat `i`, if anyone. If it can't be sent, they're gone
+    fn send(&mut self, i: usize, msg: &Message) {
+        if let Some((_, stream)) = &mut self.seats[i].stream {
+            if writeln!(stream, "{msg}").is_err() { self.seats[i].stream = None }
+        }
+    }
+    fn broadcast(&mut self, msg: &Message) {
+        for i in 0..self.seats.len() { self.send(i, msg) }
+    }
+}
+
+impl Connection {
+    pub fn connect(addr: impl ToSocketAddrs) -> io::Result<Connection> {
+        let stream = TcpStream::connect(addr)?;
+        Ok(Connection { reader: BufReader::new(stream.try_clone()?), writer: stream })
+    }
+
+    pub fn send(&mut self, msg: &Message) -> io::Result<()> {
+        writeln!(self.writer, "{msg}")
+    }
+
+    /// The next message, `None` once the server hangs up
+    pub fn recv(&mut self) -> io::Result<Option<Message>> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 { return Ok(None); }
+        line.trim_end().parse()
+            .map(Some)
+            .map_err(|e: ParseMessageError| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))
+    }
+
+    /// Another handle to write to the server with, e.g. from another thread
+    pub fn writer(&self) -> io::Result<TcpStream> {
+        self.writer.try_clone()
+    }
+
+    /// `recv` fails after waiting this long, `None` waits forever
+    pub fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        self.writer.set_read_timeout(timeout)
+    }
+}
+
+impl Message {
+    /// The first word of the message
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            Message::Hello { .. }   => "HELLO",
+            Message::Move(_)        => "MOVE",
+            Message::State          => "STATE",
+            Message::Welcome { .. } => "WELCOME",
+            Message::View(_)        => "VIEW",
+            Message::Moved { .. }   => "MOVED",
+            Message::Event(_)       => "EVENT",
+            Message::Score(_)       => "SCORE",
+            Message::Over(_)        => "OVER",
+            Message::Error(_)       => "ERROR",
+        }
+    }
+}
+
+impl RemoteView {
+    pub fn render(&self, style: CardStyle, deck: DeckKind) -> String {
+        let cards = |cards: &[Card]| cards.iter().enumerate()
+            .map(|(i, c)| format!("{}({i})", style.render(c, deck)))
+            .collect::<Vec<String>>().join(" ; ");
+
+        let mut s = format!("Score is: {}\nTurn: '{}'\nDeck has '{}' cards\nTable has cards: '{}'\n",
+                            list(&self.score), self.players[self.turn].render(style), self.deck, cards(&self.table));
+        for (seat, player) in self.players.iter().enumerate() {
+            let hand = if seat == self.seat { cards(&self.hand) } else { vec![style.back(); self.hands[seat]].join(" ") };
+            s.push_str(&format!("{} (took '{}' cards, '{}' scope): {hand}\n", player.render(style), self.captured[seat], self.scope[seat]));
+        }
+        s
+    }
+}
+
+fn parse_one<T: FromStr>(s: &str) -> Result<T, ParseMessageError> {
+    s.parse().map_err(|_| ParseMessageError(format!("'{s}' isn't valid")))
+}
+
+impl Display for Message {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(f, "{}", self.keyword())?;
+        match self {
+            Message::Hello { player, token: None }    => write!(f, " {}", player.name()),
+            Message::Hello { player, token: Some(t) } => write!(f, " {} {t}", player.name()),
+            Message::Move(mov)                        => write!(f, " {mov}"),
+            Message::State                            => Ok(()),
+            Message::Welcome { player, token }        => write!(f, " {} {token}", player.name()),
+            Message::View(v) => write!(f, " seat={} turn={} players={} hand={} table={} hands={} deck={} scope={} captured={} score={}",
+                                       v.seat, v.turn,
+                                       list(&v.players.iter().map(|p| p.name()).collect::<Vec<&str>>()),
+                                       card_list(&v.hand), card_list(&v.table), list(&v.hands), v.deck,
+                                       list(&v.scope), list(&v.captured), list(&v.score)),
+            Message::Moved { player, mov }            => write!(f, " {} {mov}", player.name()),
+            Message::Score(score)                     => write!(f, " {}", list(score)),
+            Message::Event(text) | Message::Over(text) | Message::Error(text) => write!(f, " {text}"),
+        }
+    }
+}
+
+impl FromStr for Message {
+    type Err = ParseMessageError;
+
+    fn from_str(s: &str) -> Result<Message, ParseMessageError> {
+        let (keyword, rest) = s.split_once(' ').unwrap_or((s, ""));
+        let words: Vec<&str> = rest.split_whitespace().collect();
+        let player = |w: Option<&&str>| w.ok_or_else(|| ParseMessageError(format!("{keyword} needs a player")))?
+            .parse::<PlayerKind>().map_err(|e| ParseMessageError(e.to_string()));
+
+        Ok(match keyword {
+            "HELLO"   => Message::Hello { player: player(words.first())?, token: words.get(1).map(|t| parse_one(t)).transpose()? },
+            "MOVE"    => Message::Move(rest.trim().to_string()),
+            "STATE"   => Message::State,
+            "WELCOME" => Message::Welcome {
+                player: player(words.first())?,
+                token: parse_one(words.get(1).ok_or_else(|| ParseMessageError("WELCOME needs a token".to_string()))?)?,
+            },
+            "MOVED"   => Message::Moved { player: player(words.first())?, mov: words.get(1..).unwrap_or_default().join(" ") },
+            "SCORE"   => Message::Score(parse_list(rest.trim())?),
+            "EVENT"   => Message::Event(rest.to_string()),
+            "OVER"    => Message::Over(rest.to_string()),
+            "ERROR"   => Message::Error(rest.to_string()),
+            "VIEW"    => {
+                let field = |name: &str| words.iter()
+                    .find_map(|w| w.strip_prefix(name).and_then(|w| w.strip_prefix('=')))
+                    .ok_or_else(|| ParseMessageError(format!("VIEW needs {name}")));
+                Message::View(RemoteView {
+                    seat: parse_one(field("seat")?)?,
+                    turn: parse_one(field("turn")?)?,
+                    players: parse_list(field("players")?)?,
+                    hand: parse_list(field("hand")?)?,
+                    table: parse_list(field("table")?)?,
+                    hands: parse_list(field("hands")?)?,
+                    deck: parse_one(field("deck")?)?,
+                    scope: parse_list(field("scope")?)?,
+                    captured: parse_list(field("captured")?)?,
+                    score: parse_list(field("score")?)?,
+                })
+            },
+            _ => return Err(ParseMessageError(format!("unknown message '{keyword}'"))),
+        })
+    }
+}
+
+impl Display for ParseMessageError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<String> for ParseMessageError {
+    fn from(reason: String) -> ParseMessageError {
+        ParseMessageError(reason)
+    }
+}
+
+
+// ============ TESTS ================
+#[test]
+fn messages_round_trip() {
+    let view = RemoteView {
+        seat: 1, turn: 0, players: vec![PlayerKind::Purple, PlayerKind::Green],
+        hand: ["7d", "Rs"].map(|c| c.parse().unwrap()).into(), table: vec![],
+        hands: vec![3, 2], deck: 30, scope: vec![0, 1], captured: vec![0, 4], score: vec![5, 11],
+    };
+    for msg in [Message::Hello { player: PlayerKind::Green, token: None },
+                Message::Hello { player: PlayerKind::Green, token: Some(42) },
+                Message::Move("7d;3c+4b".to_string()),
+                Message::State,
+                Message::Welcome { player: PlayerKind::Blue, token: 7 },
+                Message::View(view),
+                Message::Moved { player: PlayerKind::Purple, mov: "t7d".to_string() },
+                Message::Event("Green declared less than 10 with Ad 3c 5s (3 points)".to_string()),
+                Message::Score(vec![3, 4]),
+                Message::Over("Green won".to_string()),
+                Message::Error("it's Green's turn".to_string())] {
+        assert_eq!(msg.to_string().parse::<Message>(), Ok(msg.clone()), "{msg}");
+    }
+    assert!("HELLO Fuchsia".parse::<Message>().is_err());
+    assert!("DANCE".parse::<Message>().is_err());
+}
+
+#[test]
+fn games_over_loopback() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = Server::new(Game::from_seed(1));
+    let host = server.clone();
+    thread::spawn(move || host.run(listener));
+
+    let connect = |player, token| {
+        let mut conn = Connection::connect(addr).unwrap();
+        conn.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        conn.send(&Message::Hello { player, token }).unwrap();
+        conn
+    };
+    let recv_view = |conn: &mut Connection| match conn.recv().unwrap() {
+        Some(Message::View(view)) => view,
+        other => panic!("expected a view, got {other:?}"),
+    };
+
+    let mut purple = connect(PlayerKind::Purple, None);
+    assert!(matches!(purple.recv().unwrap(), Some(Message::Welcome { player: PlayerKind::Purple, .. })));
+    let view = recv_view(&mut purple);
+    assert_eq!(view.hand, server.game().curr_match.players[0].curr_hand);
+
+    // One seat per connection, another HELLO doesn't show another hand
+    purple.send(&Message::Hello { player: PlayerKind::Green, token: None }).unwrap();
+    assert!(matches!(purple.recv().unwrap(), Some(Message::Error(e)) if e == "you're already sitting as Purple"));
+    purple.send(&Message::State).unwrap();
+    assert_eq!(recv_view(&mut purple), view);
+
+    let mut green = connect(PlayerKind::Green, None);
+    let Some(Message::Welcome { token, .. }) = green.recv().unwrap() else { panic!("Green should be welcome") };
+    let view = recv_view(&mut green);
+    assert_eq!(view.hand, server.game().curr_match.players[1].curr_hand); // Only its own hand
+    assert_eq!(view.hands, [3, 3]);
+
+    // Taken seats, moves out of turn and illegal moves are refused
+    let mut impostor = connect(PlayerKind::Green, Some(token + 1));
+    assert!(matches!(impostor.recv().unwrap(), Some(Message::Error(_))));
+    green.send(&Message::Move("t0".to_string())).unwrap();
+    assert!(matches!(green.recv().unwrap(), Some(Message::Error(e)) if e == "it's Purple's turn"));
+    purple.send(&Message::Move("Zz".to_string())).unwrap();
+    assert!(matches!(purple.recv().unwrap(), Some(Message::Error(_))));
+
+    purple.send(&Message::Move("t0".to_string())).unwrap();
+    for conn in [&mut purple, &mut green] {
+        assert!(matches!(conn.recv().unwrap(), Some(Message::Moved { player: PlayerKind::Purple, .. })));
+        let view = recv_view(conn);
+        assert_eq!((view.turn, view.hands.clone()), (1, vec![2, 3]));
+    }
+
+    // Green drops out and comes back with its token, to the same hand
+    drop(green);
+    let mut green = connect(PlayerKind::Green, Some(token));
+    assert!(matches!(green.recv().unwrap(), Some(Message::Welcome { .. })));
+    assert_eq!(recv_view(&mut green).hand, server.game().curr_match.players[1].curr_hand);
+    green.send(&Message::Move("t0".to_string())).unwrap();
+    assert!(matches!(green.recv().unwrap(), Some(Message::Moved { player: PlayerKind::Green, .. })));
+
+    // Lines without end aren't read forever
+    let mut flood = Connection::connect(addr).unwrap();
+    flood.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+    flood.send(&Message::Move("t".repeat(MAX_LINE as usize))).unwrap();
+    assert!(!matches!(flood.recv(), Ok(Some(_))));
+
+    // Whoever sits down is told what happened in the match before they did
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let mut game = Game::with_rules(1, ScopaRules::cirulla());
+    game.curr_match.events = vec![Event::Declared { seat: 1, declaration: Declaration::Low, hand: cards(&["Ad", "3c", "5s"]) }];
+    let server = Server::new(game);
+    thread::spawn(move || server.run(listener));
+    let mut purple = Connection::connect(addr).unwrap();
+    purple.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+    purple.send(&Message::Hello { player: PlayerKind::Purple, token: None }).unwrap();
+    assert!(matches!(purple.recv().unwrap(), Some(Message::Welcome { .. })));
+    assert_eq!(purple.recv().unwrap(), Some(Message::Event("Green declared less than 10 with Ad 3c 5s (3 points)".to_string())));
+    recv_view(&mut purple);
+}
@@ -1,42 +1,67 @@
 use nom::{
     IResult,
     bytes::complete::tag,
+    character::complete::one_of,
+    combinator::{all_consuming, map, map_res, recognize},
     multi::separated_list0,
+    sequence::pair,
     branch::alt,
 };
+use cards_core::Card;
 
-use crate::ParsedMove;
+use crate::{Destination, ParsedMove, Pick};
 
 
 pub fn parse_move_internal(input: &str) -> IResult<&str, ParsedMove> {
-    alt((parse_table_drop_move, parse_taking_move))(input)
+    all_consuming(alt((parse_table_drop_move, parse_taking_move, parse_bare_move)))(input)
 }
 
 pub fn parse_table_drop_move(input: &str) -> IResult<&str, ParsedMove> {
     let (input, _) = tag("t")(input)?;
-    let (input, left) = parse_left(input)?;
+    let (input, left) = parse_pick(input)?;
 
     Ok((input, ParsedMove {
-        from: left as usize,
-        to: None,
+        from: left,
+        to: Destination::Table,
     }))
 }
 
 pub fn parse_taking_move(input: &str) -> IResult<&str, ParsedMove> {
-    let (input, left) = parse_left(input)?;
+    let (input, left) = parse_pick(input)?;
     let (input, _) = tag(";")(input)?;
     let (input, right) = parse_right(input)?;
 
     Ok((input, ParsedMove {
-        from: left as usize,
-        to: Some(right.into_iter().map(|n| n as usize).collect())
+        from: left,
+        to: Destination::Taking(right),
     }))
 }
-fn parse_left(input: &str) -> IResult<&str, u32> {
-    nom::character::complete::u32(input)
+
+/// Just the card to play, what it does is worked out from the table
+pub fn parse_bare_move(input: &str) -> IResult<&str, ParsedMove> {
+    let (input, left) = parse_pick(input)?;
+
+    Ok((input, ParsedMove {
+        from: left,
+        to: Destination::Whichever,
+    }))
+}
+
+/// A card by name (`7d`, `Rs`, `10c`...) or by its position
+fn parse_pick(input: &str) -> IResult<&str, Pick> {
+    alt((
+        map(parse_card, Pick::Card),
+        map(nom::character::complete::u32, |i| Pick::Index(i as usize)),
+    ))(input)
+}
+
+/// Italian notation, see `cards_core::Notation`
+fn parse_card(input: &str) -> IResult<&str, Card> {
+    let rank = alt((tag("10"), recognize(one_of("A23456789FCR"))));
+    map_res(recognize(pair(rank, one_of("dcbs"))), str::parse::<Card>)(input)
 }
 
-fn parse_right(input: &str) -> IResult<&str, Vec<u32>> {
-    separated_list0(tag("+"), nom::character::complete::u32)(input)
+fn parse_right(input: &str) -> IResult<&str, Vec<Pick>> {
+    separated_list0(tag("+"), parse_pick)(input)
 
 }
@@ -0,0 +1,316 @@
+//! Whole games, kept as they're played (see `Game::history`) and written down as text to be
+//! replayed later.
+//!
+//! # Format
+//! One line each, blank lines and lines starting with `#` are skipped:
+//! ```text
+//! scopa-record
+//! seed 1234
+//! rules teams=0,1 hand=3 table=4 ... mode=scopa
+//! match Purple                         # Who plays first in it
+//! deal Purple 4b,5d,6s                 # What each player was dealt first
+//! deal Green Rc,Ab,7d
+//! table Fs,2c,7b,6c
+//! move Purple t4b                      # Every move, see `Move::notation`
+//! move Green 7d;7b
+//! ...
+//! tally 0 points=4 cards=22 denari=6 scope=1 primiera=78 declared=0
+//! tally 1 points=2 cards=18 denari=4 scope=0 primiera=- declared=0
+//! score 4,2                            # Running score after the match
+//! match Green
+//! ...
+//! final 21,13
+//! ```
+//! Deals, tallies and scores are worked out again from the seed and the moves when a record is
+//! read, and have to match.
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use cards_core::*;
+
+use crate::*;
+
+/// A game from its deal to its last move
+#[derive(Clone, Debug)]
+pub struct GameRecord {
+    pub seed: u64,
+    /// As last set, see `Game::set_rules`
+    pub rules: ScopaRules,
+    pub matches: Vec<MatchRecord>,
+    /// Of each team, after the last match that was scored
+    pub points: Vec<usize>,
+}
+
+#[derive(Clone, Debug)]
+pub struct MatchRecord {
+    /// Index in `Game::players` of who played first
+    pub first_player: usize,
+    /// As first dealt, by seat
+    pub hands: Vec<Vec<Card>>,
+    pub table: Vec<Card>,
+    pub moves: Vec<Move>,
+    /// Once the match is over and scored
+    pub tally: Option<PointTally>,
+}
+
+/// The game as it was at one point of a replay: at the start of a match or after a move
+#[derive(Clone, Debug)]
+pub struct Frame {
+    pub game: Game,
+    /// When the move ended the match, how it was scored
+    pub tally: Option<PointTally>,
+}
+
+/// Why a record couldn't be read, `line` counts from 1
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecordError {
+    pub line: usize,
+    pub reason: String,
+}
+
+impl MatchRecord {
+    /// `m` as it was just dealt
+    pub fn dealt(m: &Match, first_player: usize) -> MatchRecord {
+        MatchRecord {
+            first_player,
+            hands: m.players.iter().map(|p| p.curr_hand.clone()).collect(),
+            table: m.table.iter().copied().collect(),
+            moves: vec![],
+            tally: None,
+        }
+    }
+}
+
+impl GameRecord {
+    /// Who sits where, by colour
+    pub fn players(&self) -> &[PlayerKind] {
+        &PlayerKind::ALL[..self.rules.layout.seats]
+    }
+
+    /// The game at the start and after every move, in order. A match that's over is shown with
+    /// the table given to the last taker and the points added, before the next one is dealt.
+    /// Fails on a move that can't be made, which only a record put together by hand can have
+    pub fn frames(&self) -> Result<Vec<Frame>, RecordError> {
+        let mut game = Game::with_rules(self.seed, self.rules);
+        let mut frames = vec![Frame { game: game.clone(), tally: None }];
+        for (n, mov) in self.matches.iter().flat_map(|m| &m.moves).enumerate() {
+            game.play(&mov.play())
+                .map_err(|e| RecordError { line: 0, reason: format!("move {n} ({}): {e}", mov.notation()) })?;
+            let tally = game.is_match_over();
+            if let Some(tally) = &tally {
+                game.add_points(tally);
+            } else {
+                game.next_turn();
+            }
+            frames.push(Frame { game: game.clone(), tally });
+
+            if frames.last().is_some_and(|f| f.tally.is_some()) && !game.is_over() {
+                game.rotate_dealer();
+                game.start_new_match();
+                frames.push(Frame { game: game.clone(), tally: None });
+            }
+        }
+        Ok(frames)
+    }
+}
+
+impl Display for GameRecord {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let players = self.players();
+        writeln!(f, "scopa-record")?;
+        writeln!(f, "seed {}", self.seed)?;
+        writeln!(f, "rules {}", self.rules)?;
+
+        let mut score = vec![0; self.points.len()];
+        for m in &self.matches {
+            let player_at = |seat: Seat| players[(m.first_player + seat) % players.len()].name();
+            writeln!(f, "match {}", player_at(0))?;
+            for (seat, hand) in m.hands.iter().enumerate() {
+                writeln!(f, "deal {} {}", player_at(seat), card_list(hand))?;
+            }
+            writeln!(f, "table {}", card_list(&m.table))?;
+            for mov in &m.moves {
+                writeln!(f, "move {} {}", player_at(mov.turn), mov.notation())?;
+            }
+            if let Some(tally) = &m.tally {
+                for (team, score) in score.iter_mut().enumerate() {
+                    writeln!(f, "tally {team} {}", tally_line(tally, team))?;
+                    *score += tally.points(team);
+                }
+                writeln!(f, "score {}", list(&score))?;
+            }
+        }
+        writeln!(f, "final {}", list(&self.points))
+    }
+}
+
+/// Plays the moves out from the seed, anything that doesn't match what it says is an error
+impl FromStr for GameRecord {
+    type Err = RecordError;
+
+    fn from_str(s: &str) -> Result<GameRecord, RecordError> {
+        let mut lines = s.lines().enumerate()
+            .map(|(i, line)| (i + 1, line.split('#').next().unwrap_or_default().trim()))
+            .filter(|(_, line)| !line.is_empty());
+        let mut next = |keyword: &str| match lines.next() {
+            Some((i, line)) => match line.split_once(' ').unwrap_or((line, "")) {
+                (k, rest) if k == keyword => Ok((i, rest.to_string())),
+                (k, _) => Err(RecordError { line: i, reason: format!("expected '{keyword}', found '{k}'") }),
+            },
+            None => Err(RecordError { line: 0, reason: format!("it ends before '{keyword}'") }),
+        };
+
+        next("scopa-record")?;
+        let (i, seed) = next("seed")?;
+        let seed = seed.parse().map_err(|_| RecordError { line: i, reason: format!("'{seed}' isn't a seed") })?;
+        let (i, rules) = next("rules")?;
+        let rules = rules.parse().map_err(|e: InvalidRules| RecordError { line: i, reason: e.to_string() })?;
+        let mut game = Game::with_rules(seed, rules);
+        // What a match that was just scored still needs, in order: a tally of each team and the score
+        let mut owed: Vec<String> = vec![];
+        let mut finished = false;
+
+        for (i, line) in lines {
+            let err = |reason: String| RecordError { line: i, reason };
+            let (keyword, rest) = line.split_once(' ').unwrap_or((line, ""));
+            let mut words = rest.split_whitespace();
+            if finished { return Err(err("there's nothing after 'final'".to_string())); }
+            match owed.first() {
+                Some(next) if line.split_whitespace().take(next.split(' ').count()).ne(next.split(' ')) =>
+                    return Err(err(format!("expected '{next}'"))),
+                Some(_) => { owed.remove(0); },
+                None if matches!(keyword, "tally" | "score") => return Err(err(format!("no match was just scored for '{keyword}'"))),
+                None => {},
+            }
+            let mut player = || words.next().unwrap_or_default().parse::<PlayerKind>().map_err(|e| err(e.to_string()));
+            let this_match = game.history.matches.last().expect("A game always has a match");
+
+            match keyword {
+                "match" => {
+                    let first = player()?;
+                    if game.player_at(0) != first || !this_match.moves.is_empty() {
+                        return Err(err(format!("this match is {}'s to start", game.player_at(0).name())));
+                    }
+                },
+                "deal" => {
+                    let seat = game.seat_of(player()?);
+                    if parse_list::<Card>(words.next().unwrap_or_default()).map_err(err)? != this_match.hands[seat] {
+                        return Err(err("that's not what was dealt".to_string()));
+                    }
+                },
+                "table" => if parse_list::<Card>(rest).map_err(err)? != this_match.table {
+                    return Err(err("that's not what was dealt".to_string()));
+                },
+                "move" => {
+                    if game.is_over() { return Err(err("the game is already over".to_string())); }
+                    if player()? != game.color_playing() {
+                        return Err(err(format!("it's {}'s turn", game.color_playing().name())));
+                    }
+                    let play = words.next().unwrap_or_default().into_play(&game.curr_match).map_err(|e| err(e.to_string()))?;
+                    let scored = scored_matches(&game);
+                    game.apply_move(&play).map_err(|e| err(e.to_string()))?;
+                    if scored_matches(&game) != scored {
+                        owed = (0..game.rules().layout.number_of_teams()).map(|t| format!("tally {t}"))
+                            .chain(["score".to_string()])
+                            .collect();
+                    }
+                },
+                "tally" => {
+                    let tally = game.history.matches.iter().rev().find_map(|m| m.tally.as_ref())
+                        .expect("Tallies are only owed by scored matches");
+                    let team: Team = words.next().unwrap_or_default().parse().expect("Owed tallies say their team");
+                    if words.collect::<Vec<&str>>().join(" ") != tally_line(tally, team) {
+                        return Err(err(format!("team {team} made {}", tally_line(tally, team))));
+                    }
+                },
+                "score" | "final" => {
+                    if parse_list::<usize>(rest).map_err(err)? != game.points {
+                        return Err(err(format!("the score was {}", list(&game.points))));
+                    }
+                    finished = keyword == "final";
+                },
+                _ => return Err(err(format!("unknown line '{keyword}'"))),
+            }
+        }
+        if let Some(next) = owed.first() {
+            return Err(RecordError { line: 0, reason: format!("it ends before '{next}'") });
+        }
+        if !finished {
+            return Err(RecordError { line: 0, reason: "it ends before 'final'".to_string() });
+        }
+        Ok(game.history)
+    }
+}
+
+fn scored_matches(game: &Game) -> usize {
+    game.history.matches.iter().filter(|m| m.tally.is_some()).count()
+}
+
+/// What `team` made in a match, as a `tally` line has it
+fn tally_line(tally: &PointTally, team: Team) -> String {
+    let haul = &tally.teams[team];
+    format!("points={} cards={} denari={} scope={} primiera={} declared={}",
+            tally.points(team), haul.cards, haul.denari, haul.scope,
+            haul.primiera.map_or("-".to_string(), |p| p.to_string()), haul.declared)
+}
+
+impl Display for RecordError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(f, "line {}: {}", self.line, self.reason)
+    }
+}
+
+
+// ============ TESTS ================
+#[test]
+fn records_replay_the_game() {
+    use rand::seq::SliceRandom;
+    let mut rng = seeded_rng(0);
+    let rules = ScopaRules { rotation: Rotation::LastTaker, target_score: 31, ..ScopaRules::cirulla() };
+    assert_eq!(rules.to_string().parse::<ScopaRules>(), Ok(rules));
+
+    let mut game = Game::with_rules(7, rules);
+    let outcome = play_out(&mut game, 10_000, |_, moves| moves.choose(&mut rng).cloned()).unwrap();
+    assert!(outcome.is_some());
+    let history = &game.history;
+    assert!(history.matches.len() > 1);
+    assert!(history.matches.iter().all(|m| m.tally.is_some()));
+    assert_eq!(history.points, game.points);
+
+    // Written down and read back, to the same text
+    let text = history.to_string();
+    let read: GameRecord = text.parse().unwrap();
+    assert_eq!(read.to_string(), text);
+
+    // Frames step through every move, ending where the game did
+    let frames = read.frames().unwrap();
+    let moves: usize = read.matches.iter().map(|m| m.moves.len()).sum();
+    assert_eq!(frames.len(), 1 + moves + read.matches.len() - 1);
+    assert_eq!(frames.iter().filter(|f| f.tally.is_some()).count(), read.matches.len());
+    let last = &frames.last().unwrap().game;
+    assert_eq!(last.points, game.points);
+    assert!(last.is_over());
+
+    // A record that doesn't add up is refused, saying where
+    let tampered = text.replacen("move Green", "move Purple", 1);
+    assert!(matches!(tampered.parse::<GameRecord>(), Err(RecordError { reason, .. }) if reason == "it's Green's turn"));
+    let cut = text.replace(&format!("final {}", list(&game.points)), "final 0,0");
+    assert!(cut.parse::<GameRecord>().is_err());
+    let tally = text.lines().find(|l| l.starts_with("tally 0")).unwrap();
+    let inflated = text.replacen(tally, &tally.replacen("points=", "points=1", 1), 1);
+    assert!(matches!(inflated.parse::<GameRecord>(), Err(RecordError { reason, .. }) if reason.starts_with("team 0 made")));
+
+    // Nor can tallies, scores or the final score be left out
+    let untallied = text.replacen(&format!("{tally}\n"), "", 1);
+    assert!(matches!(untallied.parse::<GameRecord>(), Err(RecordError { reason, .. }) if reason == "expected 'tally 0'"));
+    let unscored: String = text.lines().filter(|l| !l.starts_with("score")).map(|l| format!("{l}\n")).collect();
+    assert!(matches!(unscored.parse::<GameRecord>(), Err(RecordError { reason, .. }) if reason == "expected 'score'"));
+    let unfinished = text.replace(&format!("final {}\n", list(&game.points)), "");
+    assert!(matches!(unfinished.parse::<GameRecord>(), Err(RecordError { reason, .. }) if reason == "it ends before 'final'"));
+
+    // A record put together by hand can't be stepped through if its moves can't be made
+    let mut forged = read.clone();
+    forged.matches[0].moves.swap(0, 1);
+    assert!(forged.frames().is_err());
+}
@@ -0,0 +1,365 @@
+//! The rules that vary from table to table. The defaults are what this crate has always played.
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// Most people that fit around a table
+pub const MAX_SEATS: usize = 6;
+
+/// Rule toggles for a game of scopa, see `Game::set_rules`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ScopaRules {
+    /// How many play, in which teams, and how the cards are dealt
+    pub layout: Layout,
+    /// Asso piglia tutto: an ace played on a table with cards takes all of them
+    pub asso_piglia_tutto: bool,
+    /// With asso piglia tutto, an ace played while there's an ace on the table doesn't sweep, it
+    /// takes that ace like any other card would
+    pub ace_takes_ace: bool,
+    /// Presa obbligatoria: a card that can take something can't be placed on the table instead
+    pub must_take: bool,
+    /// Whether sweeping the table with an ace counts as a scopa
+    pub ace_sweep_is_scopa: bool,
+    /// Whether a scopa made with the very last card of the match counts
+    pub last_hand_scopa: bool,
+    /// Whether the re di denari is worth a point
+    pub re_bello: bool,
+    /// Whether napoli (the 1, 2, 3... of denari) is scored, full napoli included
+    pub napoli: bool,
+    /// Points needed to win the game
+    pub target_score: usize,
+    /// Who plays first in the next match
+    pub rotation: Rotation,
+    /// What cards can take
+    pub capture: Capture,
+    /// Plain scopa, or one of the games built on it
+    pub mode: Mode,
+    /// Whether a move can still be taken back once the next player has seen it, see
+    /// `Match::undo`
+    pub undo_seen_moves: bool,
+}
+
+/// Which table cards a card can take
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Capture {
+    /// Cards that add up to its value
+    #[default]
+    Sum,
+    /// Cards that make 15 together with it (scopa a quindici)
+    Fifteen,
+    /// Either cards that add up to its value, or cards that make 15 together with it
+    SumOrFifteen,
+}
+
+impl Capture {
+    pub const NAMES: [(&'static str, Capture); 3] = [
+        ("sum",            Capture::Sum),
+        ("fifteen",        Capture::Fifteen),
+        ("sum-or-fifteen", Capture::SumOrFifteen),
+    ];
+
+    pub fn by_value(self) -> bool {
+        matches!(self, Capture::Sum | Capture::SumOrFifteen)
+    }
+    pub fn by_fifteen(self) -> bool {
+        matches!(self, Capture::Fifteen | Capture::SumOrFifteen)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Mode {
+    #[default]
+    Scopa,
+    /// Cirulla, or cicera: hands are declared when dealt (see `cirulla::Declaration`), a table
+    /// dealt adding up to 15 or 30 goes to the dealer, and the grande and the piccola of denari
+    /// replace re bello and napoli
+    Cirulla,
+}
+
+impl Mode {
+    pub const NAMES: [(&'static str, Mode); 2] = [
+        ("scopa",   Mode::Scopa),
+        ("cirulla", Mode::Cirulla),
+    ];
+}
+
+/// How the first seat moves from one match to the next. Whoever sits before it deals
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Rotation {
+    /// One seat around the table: whoever played second now plays first
+    #[default]
+    Around,
+    /// The same player plays first every match
+    Fixed,
+    /// Whoever made the last take of the match plays first in the next one
+    LastTaker,
+}
+
+/// Who sits at the table and how the cards are dealt to them
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Layout {
+    pub seats: usize,
+    /// Team of each seat, going around the table. Only the first `seats` are used and the teams
+    /// are numbered from 0 without gaps
+    pub teams: [usize; MAX_SEATS],
+    /// Cards dealt to each hand, again every time all hands are empty, until the deck runs out
+    pub hand_size: usize,
+    /// Cards dealt face up on the table at the start of the match
+    pub table_size: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnknownLayout(pub String);
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnknownRotation(pub String);
+
+/// Why a `ScopaRules` couldn't be read back from its `Display`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvalidRules(pub String);
+
+/// Why `Layout::new` refused a layout
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InvalidLayout {
+    /// Scopa needs at least two at the table, and no more than `MAX_SEATS`
+    Seats(usize),
+    /// Teams have to be numbered from 0 without gaps, this one has nobody in it
+    EmptyTeam(usize),
+    /// Everybody is on the same team
+    OneTeam,
+    /// Nobody would get any cards
+    EmptyHands,
+    /// The first deal needs more than the 40 cards in the deck
+    TooManyCards(usize),
+}
+
+impl Layout {
+    /// Two players, three cards each and four on the table
+    pub const SCOPA: Layout = Layout { seats: 2, teams: [0, 1, 0, 0, 0, 0], hand_size: 3, table_size: 4 };
+    /// Four players in two partnerships (partners sit across from each other), nine cards each and
+    /// four on the table
+    pub const SCOPONE_SCIENTIFICO: Layout = Layout { seats: 4, teams: [0, 1, 0, 1, 0, 0], hand_size: 9, table_size: 4 };
+    /// Like scopone scientifico, but ten cards each and none on the table
+    pub const SCOPONE_CLASSICO: Layout = Layout { seats: 4, teams: [0, 1, 0, 1, 0, 0], hand_size: 10, table_size: 0 };
+    /// Three players, each for themselves, three cards each and four on the table
+    pub const THREE_PLAYERS: Layout = Layout { seats: 3, teams: [0, 1, 2, 0, 0, 0], hand_size: 3, table_size: 4 };
+    /// Six players in three teams of two (partners sit three seats apart), three cards each and
+    /// four on the table
+    pub const SIX_PLAYERS: Layout = Layout { seats: 6, teams: [0, 1, 2, 0, 1, 2], hand_size: 3, table_size: 4 };
+
+    pub const NAMES: [(&'static str, Layout); 5] = [
+        ("scopa",       Layout::SCOPA),
+        ("scientifico", Layout::SCOPONE_SCIENTIFICO),
+        ("classico",    Layout::SCOPONE_CLASSICO),
+        ("three",       Layout::THREE_PLAYERS),
+        ("six",         Layout::SIX_PLAYERS),
+    ];
+
+    /// One seat per entry of `teams`, which says what team each seat is on
+    pub fn new(teams: &[usize], hand_size: usize, table_size: usize) -> Result<Layout, InvalidLayout> {
+        let seats = teams.len();
+        if !(2..=MAX_SEATS).contains(&seats) { return Err(InvalidLayout::Seats(seats)); }
+
+        let mut layout = Layout { seats, teams: [0; MAX_SEATS], hand_size, table_size };
+        layout.teams[..seats].copy_from_slice(teams);
+
+        if let Some(empty) = (0..layout.number_of_teams()).find(|&t| !teams.contains(&t)) {
+            return Err(InvalidLayout::EmptyTeam(empty));
+        }
+        if layout.number_of_teams() < 2 { return Err(InvalidLayout::OneTeam); }
+        if hand_size == 0 { return Err(InvalidLayout::EmptyHands); }
+
+        let first_deal = seats * hand_size + table_size;
+        if first_deal > 40 { return Err(InvalidLayout::TooManyCards(first_deal)); }
+        Ok(layout)
+    }
+    /// The same seats and teams, dealt differently
+    pub fn with_deal(self, hand_size: usize, table_size: usize) -> Result<Layout, InvalidLayout> {
+        Layout::new(&self.teams[..self.seats], hand_size, table_size)
+    }
+
+    pub fn team_of(&self, seat: usize) -> usize {
+        self.teams[seat]
+    }
+    pub fn number_of_teams(&self) -> usize {
+        self.teams[..self.seats].iter().max().map_or(0, |t| t + 1)
+    }
+    /// Seats of everyone in `team`
+    pub fn members(&self, team: usize) -> Vec<usize> {
+        (0..self.seats).filter(|&s| self.teams[s] == team).collect()
+    }
+}
+
+impl Default for ScopaRules {
+    fn default() -> Self {
+        ScopaRules {
+            layout: Layout::SCOPA,
+            asso_piglia_tutto: true,
+            ace_takes_ace: false,
+            must_take: false,
+            ace_sweep_is_scopa: false,
+            last_hand_scopa: true,
+            re_bello: true,
+            napoli: true,
+            target_score: 21,
+            rotation: Rotation::default(),
+            capture: Capture::default(),
+            mode: Mode::default(),
+            undo_seen_moves: true,
+        }
+    }
+}
+
+impl ScopaRules {
+    /// Scopa a quindici: cards only take what makes 15 together with them, aces included
+    pub fn quindici() -> ScopaRules {
+        ScopaRules { capture: Capture::Fifteen, asso_piglia_tutto: false, ..ScopaRules::default() }
+    }
+    /// Scopa d'assi: aces sweep the table, unless there's an ace on it, and that's no scopa
+    pub fn assi() -> ScopaRules {
+        ScopaRules { asso_piglia_tutto: true, ace_takes_ace: true, ace_sweep_is_scopa: false, ..ScopaRules::default() }
+    }
+    /// Cirulla as it's usually played: captures by 15 too, no re bello nor napoli, up to 51
+    pub fn cirulla() -> ScopaRules {
+        ScopaRules {
+            capture: Capture::SumOrFifteen,
+            mode: Mode::Cirulla,
+            re_bello: false,
+            napoli: false,
+            target_score: 51,
+            ..ScopaRules::default()
+        }
+    }
+}
+
+/// The name `value` goes by in `names`
+fn name_of<T: PartialEq>(names: &[(&'static str, T)], value: &T) -> &'static str {
+    names.iter().find(|(_, v)| v == value).map_or("?", |(name, _)| name)
+}
+
+/// The value `name` stands for in `names`
+fn named<T: Copy>(names: &[(&'static str, T)], name: &str, what: &str) -> Result<T, InvalidRules> {
+    names.iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, value)| *value)
+        .ok_or_else(|| InvalidRules(format!("unknown {what} '{name}'")))
+}
+
+/// Every rule as `key=value`, e.g. `teams=0,1 hand=3 table=4 ... mode=scopa`. `FromStr` reads it
+/// back, leaving out a rule leaves it as the default
+impl Display for ScopaRules {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let teams: Vec<String> = self.layout.teams[..self.layout.seats].iter().map(|t| t.to_string()).collect();
+        write!(f, "teams={} hand={} table={} asso_piglia_tutto={} ace_takes_ace={} must_take={} ace_sweep_is_scopa={} \
+                   last_hand_scopa={} re_bello={} napoli={} target={} rotation={} capture={} mode={} undo_seen_moves={}",
+               teams.join(","), self.layout.hand_size, self.layout.table_size,
+               self.asso_piglia_tutto, self.ace_takes_ace, self.must_take, self.ace_sweep_is_scopa,
+               self.last_hand_scopa, self.re_bello, self.napoli, self.target_score,
+               name_of(&Rotation::NAMES, &self.rotation), name_of(&Capture::NAMES, &self.capture),
+               name_of(&Mode::NAMES, &self.mode), self.undo_seen_moves)
+    }
+}
+
+impl FromStr for ScopaRules {
+    type Err = InvalidRules;
+
+    fn from_str(s: &str) -> Result<ScopaRules, InvalidRules> {
+        let mut rules = ScopaRules::default();
+        let mut teams = rules.layout.teams[..rules.layout.seats].to_vec();
+        let (mut hand, mut table) = (rules.layout.hand_size, rules.layout.table_size);
+
+        for field in s.split_whitespace() {
+            let (key, value) = field.split_once('=').ok_or_else(|| InvalidRules(format!("'{field}' isn't key=value")))?;
+            let flag = || value.parse().map_err(|_| InvalidRules(format!("{key} is true or false, not '{value}'")));
+            let number = || value.parse().map_err(|_| InvalidRules(format!("{key} is a number, not '{value}'")));
+            match key {
+                "teams"              => teams = value.split(',')
+                    .map(|t| t.parse().map_err(|_| InvalidRules(format!("teams are numbers, not '{t}'"))))
+                    .collect::<Result<Vec<usize>, InvalidRules>>()?,
+                "hand"               => hand = number()?,
+                "table"              => table = number()?,
+                "asso_piglia_tutto"  => rules.asso_piglia_tutto = flag()?,
+                "ace_takes_ace"      => rules.ace_takes_ace = flag()?,
+                "must_take"          => rules.must_take = flag()?,
+                "ace_sweep_is_scopa" => rules.ace_sweep_is_scopa = flag()?,
+                "last_hand_scopa"    => rules.last_hand_scopa = flag()?,
+                "re_bello"           => rules.re_bello = flag()?,
+                "napoli"             => rules.napoli = flag()?,
+                "target"             => rules.target_score = number()?,
+                "rotation"           => rules.rotation = named(&Rotation::NAMES, value, "rotation")?,
+                "capture"            => rules.capture = named(&Capture::NAMES, value, "capture")?,
+                "mode"               => rules.mode = named(&Mode::NAMES, value, "mode")?,
+                "undo_seen_moves"    => rules.undo_seen_moves = flag()?,
+                _                    => return Err(InvalidRules(format!("unknown rule '{key}'"))),
+            }
+        }
+        rules.layout = Layout::new(&teams, hand, table).map_err(|e| InvalidRules(e.to_string()))?;
+        Ok(rules)
+    }
+}
+
+impl Default for Layout {
+    fn default() -> Self { Layout::SCOPA }
+}
+
+impl FromStr for Layout {
+    type Err = UnknownLayout;
+
+    fn from_str(s: &str) -> Result<Layout, UnknownLayout> {
+        Layout::NAMES.iter()
+            .find(|(name, _)| *name == s)
+            .map(|(_, layout)| *layout)
+            .ok_or_else(|| UnknownLayout(s.to_string()))
+    }
+}
+
+impl Rotation {
+    pub const NAMES: [(&'static str, Rotation); 3] = [
+        ("around",     Rotation::Around),
+        ("fixed",      Rotation::Fixed),
+        ("last-taker", Rotation::LastTaker),
+    ];
+}
+
+impl FromStr for Rotation {
+    type Err = UnknownRotation;
+
+    fn from_str(s: &str) -> Result<Rotation, UnknownRotation> {
+        Rotation::NAMES.iter()
+            .find(|(name, _)| *name == s)
+            .map(|(_, rotation)| *rotation)
+            .ok_or_else(|| UnknownRotation(s.to_string()))
+    }
+}
+
+impl Display for UnknownLayout {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let names: Vec<&str> = Layout::NAMES.iter().map(|(name, _)| *name).collect();
+        write!(f, "unknown variant '{}', expected one of: {}", self.0, names.join(", "))
+    }
+}
+
+impl Display for UnknownRotation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let names: Vec<&str> = Rotation::NAMES.iter().map(|(name, _)| *name).collect();
+        write!(f, "unknown rotation '{}', expected one of: {}", self.0, names.join(", "))
+    }
+}
+
+impl Display for InvalidRules {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(f, "invalid rules: {}", self.0)
+    }
+}
+
+impl Display for InvalidLayout {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            InvalidLayout::Seats(n)        => write!(f, "scopa is played by 2 to {MAX_SEATS}, not {n}"),
+            InvalidLayout::EmptyTeam(t)    => write!(f, "team {t} has nobody in it, teams are numbered from 0"),
+            InvalidLayout::OneTeam         => write!(f, "everybody is on the same team"),
+            InvalidLayout::EmptyHands      => write!(f, "hands must have at least one card"),
+            InvalidLayout::TooManyCards(n) => write!(f, "the first deal needs {n} cards, the deck only has 40"),
+        }
+    }
+}
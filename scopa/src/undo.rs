@@ -0,0 +1,157 @@
+//! Taking moves back, and making them again. Only within the match they were made in, once it's
+//! scored it stays that way.
+
+use std::fmt::{Display, Formatter};
+
+use cards_core::*;
+
+use crate::*;
+
+/// Everything `Match::play` can change, as it was before a move
+#[derive(Clone, Debug)]
+pub(crate) struct Snapshot {
+    turn: Seat,
+    players: Vec<Player>,
+    deck: Deck,
+    table: Deck,
+    events: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UndoError {
+    NothingToUndo,
+    NothingToRedo,
+    /// The next player has already seen the move, and `ScopaRules::undo_seen_moves` is off
+    Seen,
+}
+
+impl Match {
+    /// Keep what's about to change, right before a move is made. Whatever was undone can't be
+    /// redone anymore
+    pub(crate) fn save_for_undo(&mut self) {
+        self.undo_stack.push(Snapshot {
+            turn: self.turn,
+            players: self.players.clone(),
+            deck: self.deck.clone(),
+            table: self.table.clone(),
+            events: self.events.len(),
+        });
+        self.redo_stack.clear();
+    }
+
+    /// Take back the last move: hands, table, piles, scope and any cards dealt after it are as
+    /// they were before it, and it's the turn of whoever made it again. Only through `Game::undo`,
+    /// which keeps the game's history in step
+    pub(crate) fn undo(&mut self) -> Result<Move, UndoError> {
+        if self.undoable() == 0 {
+            return Err(if self.moves.is_empty() { UndoError::NothingToUndo } else { UndoError::Seen });
+        }
+        let snapshot = self.undo_stack.pop().expect("Every move has a snapshot");
+        let undone = self.moves.pop().expect("Every snapshot has a move");
+
+        self.turn = snapshot.turn;
+        self.players = snapshot.players;
+        self.deck = snapshot.deck;
+        self.table = snapshot.table;
+        self.events.truncate(snapshot.events);
+        self.seen = self.seen.min(self.moves.len());
+        self.redo_stack.push(undone.play());
+        Ok(undone)
+    }
+
+    /// Make the last move taken back again. Like `play`, the turn isn't toggled. Only through
+    /// `Game::redo`
+    pub(crate) fn redo(&mut self) -> Result<Move, UndoError> {
+        let play = self.redo_stack.pop().ok_or(UndoError::NothingToRedo)?;
+        let later = std::mem::take(&mut self.redo_stack);
+        let made = self.play(&play).expect("Undone moves can be made again");
+        self.redo_stack = later;
+        Ok(made)
+    }
+
+    /// Whoever's turn it is has been shown the table, and so every move so far. See
+    /// `ScopaRules::undo_seen_moves`
+    pub fn mark_seen(&mut self) {
+        self.seen = self.moves.len();
+    }
+
+    /// How many of the last moves can be taken back right now
+    pub fn undoable(&self) -> usize {
+        if self.rules.undo_seen_moves { self.moves.len() } else { self.moves.len() - self.seen }
+    }
+}
+
+impl Game {
+    /// Take back the last move of the match, see `Match::undo`
+    pub fn undo(&mut self) -> Result<Move, UndoError> {
+        let undone = self.curr_match.undo()?;
+        self.history.matches.last_mut().expect("A game always has a match").moves.pop();
+        self.last_move = self.curr_match.moves.last().cloned();
+        self.who_won_last_round = self.curr_match.moves.iter().rev()
+            .find(|m| m.cards_taken.is_some())
+            .map_or(0, |m| m.turn);
+        Ok(undone)
+    }
+
+    /// Make the last move taken back again, see `Match::redo`
+    pub fn redo(&mut self) -> Result<Move, UndoError> {
+        let made = self.curr_match.redo()?;
+        self.record(&made);
+        Ok(made)
+    }
+}
+
+impl Display for UndoError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            UndoError::NothingToUndo => write!(f, "there's nothing to undo this match"),
+            UndoError::NothingToRedo => write!(f, "there's nothing to redo"),
+            UndoError::Seen          => write!(f, "the next player has already seen that move"),
+        }
+    }
+}
+
+
+// ============ TESTS ================
+#[test]
+fn moves_can_be_taken_back() {
+    let mut game = Game::from_seed(0);
+    game.curr_match = dealt_match(ScopaRules::default(), &["7d"], &["3c", "4b"]);
+    game.curr_match.players[1].curr_hand = cards(&["Rs"]);
+    let before = game.curr_match.clone();
+    assert_eq!(game.undo().unwrap_err(), UndoError::NothingToUndo);
+
+    // A scopa and the redeal after it, all taken back
+    game.make_move("7d;3c+4b").unwrap();
+    game.next_turn();
+    assert_eq!((game.who_won_last_round, game.curr_match.players[0].scope), (0, 1));
+    game.make_move("Rs").unwrap();
+    assert_eq!(game.curr_match.players[0].curr_hand.len(), 3); // Redealt
+    game.next_turn();
+
+    assert_eq!(game.undo().unwrap().card_played, card("Rs"));
+    assert_eq!(game.undo().unwrap().card_played, card("7d"));
+    let m = &game.curr_match;
+    assert_eq!((m.turn, m.deck.clone(), m.table.clone()), (0, before.deck.clone(), before.table.clone()));
+    assert_eq!(m.players.iter().map(|p| (&p.curr_hand, p.pile.len(), p.scope)).collect::<Vec<_>>(),
+               before.players.iter().map(|p| (&p.curr_hand, p.pile.len(), p.scope)).collect::<Vec<_>>());
+    assert_eq!((game.who_won_last_round, game.history.matches[0].moves.len()), (0, 0));
+
+    // Redone as it was, until a new move is made
+    assert_eq!(game.redo().unwrap().cards_taken, Some(vec![card("3c"), card("4b")]));
+    assert_eq!((game.curr_match.players[0].scope, game.curr_match.turn), (1, 0));
+    game.next_turn();
+    game.make_move("tRs").unwrap();
+    assert_eq!(game.redo().unwrap_err(), UndoError::NothingToRedo);
+
+    // Once seen, moves stay made unless the rules say otherwise
+    game.next_turn();
+    game.curr_match.mark_seen();
+    assert_eq!(game.curr_match.undoable(), 2);
+    game.set_rules(ScopaRules { undo_seen_moves: false, ..*game.rules() });
+    assert_eq!(game.undo().unwrap_err(), UndoError::Seen);
+    let play = game.legal_moves()[0].clone();
+    game.play(&play).unwrap();
+    assert_eq!(game.curr_match.undoable(), 1);
+    assert!(game.undo().is_ok());
+}
@@ -0,0 +1,193 @@
+//! What each seat gets to see of a match. The hands and the deck's order are secret, everything
+//! that's been played isn't: it's either on the table or in someone's pile.
+
+use cards_core::*;
+use rand::seq::SliceRandom;
+
+use crate::*;
+
+/// What one seat may know about a match: its own hand and everything that has been played, but
+/// not the other hands (a partner's included) or the order of the deck
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlayerView {
+    /// `None` for spectators, who don't get to see any hand
+    pub seat: Option<Seat>,
+    pub turn: Seat,
+    pub hand: Vec<Card>,
+    pub table: Vec<Card>,
+    /// Team of each seat
+    pub teams: Vec<Team>,
+    /// How many cards each seat is holding
+    pub hand_sizes: Vec<usize>,
+    pub deck_left: usize,
+    /// How many cards each seat has taken
+    pub captured: Vec<usize>,
+    /// What each seat has taken, everyone saw it being taken
+    pub piles: Vec<Vec<Card>>,
+    pub scope: Vec<usize>,
+    /// Every card this seat has seen: its hand, the table and whatever was taken
+    pub seen: CardSet,
+    /// Declarations are made out loud, see `Event`
+    pub events: Vec<Event>,
+    pub rules: ScopaRules,
+}
+
+impl Match {
+    pub fn view(&self, seat: Seat) -> PlayerView {
+        let hand = self.players[seat].curr_hand.clone();
+        let spectator = self.spectator_view();
+        PlayerView { seat: Some(seat), seen: spectator.seen | hand.iter().collect(), hand, ..spectator }
+    }
+
+    pub fn spectator_view(&self) -> PlayerView {
+        let table: Vec<Card> = self.table.iter().copied().collect();
+        let mut seen: CardSet = table.iter().collect();
+        for player in &self.players { seen = seen | CardSet::from(&player.pile) }
+
+        PlayerView {
+            seat: None,
+            turn: self.turn,
+            hand: vec![],
+            table,
+            teams: self.teams.clone(),
+            hand_sizes: self.players.iter().map(|p| p.curr_hand.len()).collect(),
+            deck_left: self.deck.len(),
+            captured: self.players.iter().map(|p| p.pile.len()).collect(),
+            piles: self.players.iter().map(|p| p.pile.iter().copied().collect()).collect(),
+            scope: self.players.iter().map(|p| p.scope).collect(),
+            seen,
+            events: self.events.clone(),
+            rules: self.rules,
+        }
+    }
+}
+
+impl PlayerView {
+    /// Cards that could be in another hand or still in the deck
+    pub fn unknown(&self) -> CardSet {
+        CardSet::full(DeckKind::Napoletane) - self.seen
+    }
+
+    /// Cards `seat` declared and hasn't played yet, so everyone knows it's holding them
+    pub fn declared(&self, seat: Seat) -> Vec<Card> {
+        self.events.iter()
+            .filter_map(|e| match e {
+                Event::Declared { seat: s, hand, .. } if *s == seat => Some(hand),
+                _ => None,
+            })
+            .flatten()
+            .filter(|c| !self.seen.contains(c))
+            .copied()
+            .collect()
+    }
+
+    /// A match that looks just like this one from where this seat sits, with the cards it can't
+    /// see dealt at random to the other hands and the deck. Declared cards stay with whoever
+    /// declared them
+    pub fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> Match {
+        let declared: Vec<Vec<Card>> = (0..self.hand_sizes.len())
+            .map(|seat| if self.seat == Some(seat) { vec![] } else { self.declared(seat) })
+            .collect();
+        let mut hidden: Vec<Card> = (self.unknown() - declared.iter().flatten().collect()).iter().collect();
+        hidden.shuffle(rng);
+
+        let players = (0..self.hand_sizes.len())
+            .map(|seat| Player {
+                curr_hand: if self.seat == Some(seat) { self.hand.clone() }
+                           else {
+                               let guessed = self.hand_sizes[seat] - declared[seat].len();
+                               [declared[seat].clone(), hidden.split_off(hidden.len() - guessed)].concat()
+                           },
+                pile: Deck(self.piles[seat].iter().copied().collect()),
+                scope: self.scope[seat],
+            })
+            .collect();
+        debug_assert_eq!(hidden.len(), self.deck_left);
+
+        Match {
+            turn: self.turn,
+            players,
+            teams: self.teams.clone(),
+            deck: Deck(hidden.into()),
+            table: Deck(self.table.iter().copied().collect()),
+            rules: self.rules,
+            events: self.events.clone(),
+            moves: vec![], // Nothing to take back in a guess
+            undo_stack: vec![],
+            redo_stack: vec![],
+            seen: 0,
+        }
+    }
+
+    pub fn render(&self, style: CardStyle, deck: DeckKind) -> String {
+        let cards = |cards: &[Card]| cards.iter().enumerate()
+            .map(|(i, c)| format!("{}({i})", style.render(c, deck)))
+            .collect::<Vec<String>>().join(" ; ");
+        let backs = |n: usize| vec![style.back(); n].join(" ");
+
+        let mut s = format!("Turn: '{}'\nDeck has '{}' cards\nTable has cards: '{}'\n",
+                            seat_name(self.turn), self.deck_left, cards(&self.table));
+        for seat in 0..self.hand_sizes.len() {
+            let hand = if self.seat == Some(seat) { cards(&self.hand) }
+                       else { backs(self.hand_sizes[seat]) };
+            s.push_str(&format!("{} (team {}, took '{}' cards, '{}' scope): {hand}\n",
+                                seat_name(seat), self.teams[seat], self.captured[seat], self.scope[seat]));
+        }
+        for event in &self.events {
+            s.push_str(&format!("{}\n", event.render(style, deck)));
+        }
+        s
+    }
+}
+
+impl Game {
+    /// What `player` gets to see of the current match
+    pub fn view(&self, player: PlayerKind) -> PlayerView {
+        self.curr_match.view(self.seat_of(player))
+    }
+}
+
+impl HiddenInformation for Game {
+    type View = PlayerView;
+
+    fn view(&self, player: PlayerKind) -> PlayerView {
+        Game::view(self, player)
+    }
+}
+
+
+// ============ TESTS ================
+#[test]
+fn views_hide_the_other_hand() {
+    let mut m = Match::from_seed(5);
+    let not_an_ace = *m.players[0].curr_hand.iter().find(|c| c.value() != 1).unwrap();
+    m.play(&Play::Place(not_an_ace)).unwrap();
+
+    let view = m.view(1);
+    assert_eq!(view.hand, m.players[1].curr_hand);
+    assert_eq!(view.hand_sizes, [2, 3]);
+    assert_eq!(view.table.len(), 5);
+
+    let hidden: CardSet = m.players[0].curr_hand.iter().chain(m.deck.iter()).collect();
+    assert_eq!(view.unknown(), hidden);
+    assert!(!view.render(CardStyle::Ascii, DeckKind::default()).contains(&m.players[0].curr_hand[0].notation(Notation::Italian)));
+
+    let spectator = m.spectator_view();
+    assert!(spectator.hand.is_empty());
+    assert_eq!(spectator.unknown().len(), 40 - 5);
+
+    // A declared hand is known to all, until its cards are played
+    let mut m = dealt_match(ScopaRules::cirulla(), &["Ad", "3c", "5s"], &["4b", "Rd"]);
+    m.players[1].curr_hand = cards(&["7d", "2c", "6s"]);
+    m.events = vec![Event::Declared { seat: 0, declaration: Declaration::Low, hand: cards(&["Ad", "3c", "5s"]) }];
+    let dealt: CardSet = cards(&["Ad", "3c", "5s", "7d", "2c", "6s", "4b", "Rd"]).iter().collect();
+    m.deck = Deck((CardSet::full(DeckKind::Napoletane) - dealt).iter().collect());
+    m.play(&Play::Place(card("5s"))).unwrap();
+    let view = m.view(1);
+    assert_eq!(view.declared(0), cards(&["Ad", "3c"]));
+    for seed in 0..5 {
+        let sampled = view.sample(&mut seeded_rng(seed));
+        assert_eq!(sampled.players[0].curr_hand, cards(&["Ad", "3c"]));
+        assert_eq!(sampled.players[1].curr_hand, m.players[1].curr_hand);
+    }
+}
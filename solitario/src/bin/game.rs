@@ -1,4 +1,5 @@
 use solitario::*;
+use cards_core::arg_parsed;
 
 use std::io;
 use std::io::Write;
@@ -12,12 +13,18 @@ fn print_syntax_cheatsheet(equals_string: &str) {
 }
 
 fn main() {
-    let mut table = Table::new();
+    // `--seed N` replays the exact same deal
+    let seed = arg_parsed("--seed").unwrap_or_else(cards_core::random_seed);
+    let mut table = Table::from_seed(seed);
+    if let Some(style) = arg_parsed("--style") {
+        table.style = style;
+    }
     let mut move_text_buffer = String::new();
 
     let equals_string = "=".repeat(HOW_MANY_EQUALS);
 
     print_syntax_cheatsheet(&equals_string);
+    println!("Seed is '{seed}', pass `--seed {seed}` to replay this deal");
 
     loop {
         //print!("\x1B[2J"); // this make the errors not show up lmao
@@ -37,7 +44,7 @@ fn main() {
 
 
         if let Err(e) = table.make_move(move_text_buffer.trim()) {
-            println!("Error: \x1B[1;41m{e}\x1B[0m");
+            println!("Error: {}", table.style.ansi("1;41", &e.to_string()));
         }
 
         if table.has_won() {
@@ -9,4 +9,9 @@ fn main() {
     println!("Size of table is: {:?}B", std::mem::size_of::<Table>());
     println!("Size of card is: {:?}B", std::mem::size_of::<Card>());
     println!("Size of move is: {:?}B", std::mem::size_of::<ParsedMove>());
+
+    match solve_game(&table) {
+        Some(moves) => println!("Solved in {} moves: {moves:?}", moves.len()),
+        None        => println!("No win found within {SEARCH_LIMIT} tables"),
+    }
 }
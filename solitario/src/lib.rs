@@ -6,6 +6,9 @@ pub use parse::*;
 pub mod solver;
 pub use solver::*;
 
+mod view;
+pub use view::*;
+
 pub use parse::SYNTAX_CHEATSHEET;
 
 const RED_SUITS: [Suit; 2]   = [Suit::Denari, Suit::Spade];
@@ -13,6 +16,9 @@ const BLACK_SUITS: [Suit; 2] = [Suit::Coppe, Suit::Bastoni];
 
 pub const UNKNOWN_CARD: &str = "---";
 
+/// Solitario is only played with the full French deck
+pub const DECKS: [DeckKind; 1] = [DeckKind::French];
+
 #[derive(Clone, Debug)]
 pub struct Table {
     piles: [GamePile; 7],
@@ -20,6 +26,7 @@ pub struct Table {
     passed_stack: Deck,
     aces: [AcePile; 4],
     moves: usize,
+    pub style: CardStyle,
 }
 
 #[derive(Clone, Debug, Default)]
@@ -53,12 +60,9 @@ impl GamePile {
     /// Get nth revealed (0 is lowest value, 1 is closer towards the K, etc.)
     /// `n` is the index, like in `.get` or indexing methods
     fn get_nth_revealed(&self, n: usize) -> Option<&Card> {
-        dbg!(self.revealed, &self.cards, n);
         if (self.revealed as usize) < n || self.cards.len() <= n { return None; }
 
-        dbg!(
         self.cards.iter().rev().nth(n) // O(n) also
-        )
     }
 
     fn pop_tail_of_revealed(&mut self) -> Option<Card> {
@@ -85,12 +89,17 @@ impl GamePile {
 
 impl AcePile {
     fn add_card(&mut self, card: Card) -> Result<(), IllegalAcePileAdd> {
+        if !self.accepts(&card) { return Err(IllegalAcePileAdd) }
+        self.add_card_unchecked(card);
+        Ok(())
+    }
+    /// Aces go on empty piles, then the rest of the suit in order
+    fn accepts(&self, card: &Card) -> bool {
         match (self.cards.iter().last(), card) {
-            (None, Card { number: CardNum::Numeric(1), ..}) => self.add_card_unchecked(card),
-            (Some(a@Card { suit: s_a, .. }), b@Card { suit: s_b, .. }) if *s_a == s_b && a.value_fr() + 1 == b.value_fr() => self.add_card_unchecked(card),
-            _ => return Err(IllegalAcePileAdd),
+            (None, Card { number: CardNum::Numeric(1), ..}) => true,
+            (Some(a@Card { suit: s_a, .. }), b@Card { suit: s_b, .. }) => *s_a == *s_b && a.value_fr() + 1 == b.value_fr(),
+            _ => false,
         }
-        Ok(())
     }
     fn add_card_unchecked(&mut self, card: Card) {
         self.cards.push(card);
@@ -127,6 +136,12 @@ pub enum MoveMakingError {
     StackIsEmpty,
     #[error("while moving the game piles: {0}")]
     MovingGamePile(#[from] GamePileMovingError),
+    #[error("game pile or ace pile out of range")]
+    OutOfRange,
+    #[error("'{0:?}' isn't made on the table, only `make_move` handles it")]
+    NotATableMove(ParsedMove),
+    #[error("undoing is not yet implemented")]
+    UndoNotImplemented,
 }
 
 #[derive(thiserror::Error, Debug, Clone)]
@@ -145,7 +160,13 @@ pub enum GamePileMovingError {
 
 impl Table {
     pub fn new() -> Self {
-        let mut deck = Card::shuffled_french_deck();
+        Self::new_with(&mut rand::thread_rng())
+    }
+    pub fn from_seed(seed: u64) -> Self {
+        Self::new_with(&mut seeded_rng(seed))
+    }
+    pub fn new_with<R: rand::Rng + ?Sized>(rng: &mut R) -> Self {
+        let mut deck = Card::shuffled_french_deck_with(rng);
         let mut piles = std::array::from_fn(|_i| GamePile::default());
         for (p, pile) in piles.iter_mut().enumerate() {
             pile.revealed = 1;
@@ -159,20 +180,38 @@ impl Table {
                stack: deck,
                passed_stack: Deck::new(),
                aces: std::array::from_fn(|_i| AcePile::default()),
-               moves: 0
+               moves: 0,
+               style: CardStyle::FrenchPip,
         }
     }
 
     pub fn make_move(&mut self, m: &str) -> Result<(), MoveMakingError> {
         use ParsedMove as PM;
         match parse_move(m)? {
-            PM::Undo => {
-                println!("\x1B[1;41mUndoing is not yet implemented\x1B[0m");
-            },
+            PM::Undo => Err(MoveMakingError::UndoNotImplemented),
             PM::Quit => {
                 println!("quit signal received, quitting...");
                 std::process::exit(0);
             },
+            mov => self.apply(mov),
+        }
+    }
+
+    /// Make a move that changes the table (everything but `Undo` and `Quit`)
+    pub fn apply(&mut self, mov: ParsedMove) -> Result<(), MoveMakingError> {
+        use ParsedMove as PM;
+        let (piles, aces) = match mov {
+            PM::MoveFromStackToPile(p)                => (p, 0),
+            PM::MoveFromStackToAce(a)                 => (0, a),
+            PM::MoveFromPileToAce { pile, ace }       => (pile, ace),
+            PM::MoveFromAceToPile { ace, pile }       => (pile, ace),
+            PM::MoveFromPileToPile { from, to, .. }   => (from.max(to), 0),
+            _                                         => (0, 0),
+        };
+        if piles >= 7 || aces >= 4 { return Err(MoveMakingError::OutOfRange) }
+
+        match mov {
+            PM::Undo | PM::Quit => return Err(MoveMakingError::NotATableMove(mov)),
             PM::RevealNextOfStack => {
                 if self.stack.is_empty() {
                     std::mem::swap(&mut self.stack, &mut self.passed_stack);
@@ -214,7 +253,7 @@ impl Table {
                 let _ = self.aces[ace as usize].pop();
             },
             PM::Cycle => {
-                while !self.stack.is_empty() { self.make_move("next")?; }
+                while !self.stack.is_empty() { self.apply(PM::RevealNextOfStack)?; }
 
                 if self.moves > 0 { self.moves -= 1; } // Uncount the `cycle` command, it's unintuitive and wrong
             }
@@ -237,12 +276,10 @@ impl Table {
 
         
         if legality_check(from_base, to_tail) {
-            eprintln!("Legality/King check passed: {:?} and {:?}", from_base, to_tail);
             if amount > from.revealed { return Err(GamePileMovingError::NotEnoughRevealedCards(amount)) };
 
             let removal_index = from.cards.len() - amount as usize; // len varies so we store it here
             for _ in 0..amount {
-                dbg!(&from.cards);
                 let c = from.cards.remove(removal_index);
                 to.cards.push(c);
             }
@@ -262,7 +299,7 @@ impl Table {
         }
     }
     pub fn has_won(&self) -> bool {
-        self.piles.iter().map(|p| p.cards.len() == p.revealed as usize).all(|b| b)
+        self.piles.iter().all(|p| p.cards.len() <= p.revealed as usize) // Empty piles keep `revealed` at 1
     }
 }
 
@@ -271,7 +308,6 @@ impl Table {
 /// If the base is None, added must be (any) K. If not, their values must be sequential with
 /// alternating suits.
 fn legality_check(added: &Card, base_opt: Option<&Card>) -> bool {
-    dbg!(added, base_opt);
     if let Some(base) = base_opt {
         (added.value_fr() + 1 == base.value_fr()) 
             && !((RED_SUITS.contains(&base.suit) && RED_SUITS.contains(&added.suit))
@@ -286,87 +322,10 @@ fn legality_check(added: &Card, base_opt: Option<&Card>) -> bool {
 use std::fmt::Display;
 use std::fmt::Formatter;
 
-fn print_card_fr(c: &Card) -> String {
-    let (s, col) = match c.suit {
-        Suit::Spade   => ("♥", "🟥"),
-        Suit::Denari  => ("♦", "🟥"),
-        Suit::Coppe   => ("♣", "⬛"),
-        Suit::Bastoni => ("♠", "⬛"),
-    };
-
-    let num = match c.number {
-        CardNum::Numeric(1)  => "A".to_string(),
-        CardNum::Numeric(10) => "X".to_string(), // '10' is two characters, messes up the alignment
-        CardNum::Numeric(n)  =>  n.to_string(),
-        CardNum::Fante       => "J".to_string(),
-        CardNum::Cavallo     => "Q".to_string(),
-        CardNum::Re          => "K".to_string(),
-    };
-
-    format!("{}{}{}", s, num, col)  
-}
-
 impl Display for Table {
+    /// Only draws what the player is allowed to see, see `TableView`
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
-        let mut s: String = String::new();
-        s.push_str(&format!("Number of moves made is: \x1B[1m'{}'\x1B[0m\n\n", self.moves));
-        s.push_str(&format!("\x1B[1mStack:\x1B[0m Top is {} ---- ({} cards upside down, {} passed)\n\n",
-            self.stack.top().map(print_card_fr).unwrap_or("--".to_string()),
-            self.stack.len(),
-            self.passed_stack.len(),
-            ));
-
-        let print_ace = |i: usize| self.aces[i]
-                                       .cards
-                                       .iter()
-                                       .last() // O(n) but prettier code :3
-                                       .map(print_card_fr)
-                                       .unwrap_or(UNKNOWN_CARD.to_string());
-
-        s.push_str(&format!("          \t{}\t{}\t{}\t{}\n", 
-                            "[0]",
-                            "[1]",
-                            "[2]",
-                            "[3]"));
-
-        s.push_str(&format!("\x1B[1mAce piles:\x1B[0m\t{}\t{}\t{}\t{}\n\n",
-                            print_ace(0),
-                            print_ace(1),
-                            print_ace(2),
-                            print_ace(3)));
-
-        s.push_str("\x1B[1mMain area:\x1B[0m\n");
-
-        let max_index: usize = self.piles.iter()
-            .map(|p| p.cards.len()) // All lens
-            .max().unwrap()         // Max len
-            .max(1) - 1;            // Clamp to 1, turn into index
-
-        s.push_str(&(0..7).map(|i| format!("[{i}]")).collect::<Vec<String>>().join("\t"));
-        s.push('\n');
-        s.push_str(&(0..7).map(|_| "===".to_string()).collect::<Vec<String>>().join("\t"));
-        s.push('\n');
-
-        let mut depth = 0;
-        while depth <= max_index {
-            for GamePile { cards, revealed } in &self.piles {
-                if cards.get(depth).is_none() {
-                    // Nothing
-                } else if let (true, Some(card)) = (*revealed as usize >= cards.len()-depth,
-                                                    cards.get(depth)) {
-                    s.push_str(&print_card_fr(card));
-                } else {
-                    s.push_str(UNKNOWN_CARD);
-                }
-
-                s.push('\t');
-            }
-            depth += 1;
-            s.push('\n');
-        }
-        s.push_str("\n\n");
-
-        write!(f, "{s}")
+        write!(f, "{}", self.view().render(self.style))
     }
 }
 
@@ -374,6 +333,24 @@ impl Default for Table {
     fn default() -> Self { Self::new() }
 }
 
+impl TurnBased for Table {
+    type Player = ();
+    type Move = ParsedMove;
+    type Error = MoveMakingError;
+
+    fn current_player(&self) {}
+    fn legal_moves(&self) -> Vec<ParsedMove> {
+        get_legal_moves(self)
+    }
+    fn apply_move(&mut self, mov: &ParsedMove) -> Result<(), MoveMakingError> {
+        self.apply(*mov)
+    }
+    /// Only ever a win: a stuck table just runs out of useful moves
+    fn outcome(&self) -> Option<Outcome<()>> {
+        self.has_won().then_some(Outcome::Winner(()))
+    }
+}
+
 
 // ============ TESTS ================
 #[test]
@@ -447,7 +424,6 @@ fn suit_match_legality() {
 
     for (five, six) in couples {
         let mut table = Table::new();
-        dbg!(&five, &six);
         table.piles[0].cards[0] = five;
         table.piles[1].cards[1] = six;
 
@@ -462,13 +438,49 @@ fn suit_match_legality() {
 #[test]
 fn king_to_empty_pile() {
     let mut table = Table::new();
-    table.piles = std::array::from_fn(|_i| Pile::default());
+    table.piles = std::array::from_fn(|_i| GamePile::default());
     table.piles[0].cards.push(Card::new_fr(Suit::Coppe, 13));
     table.piles[0].revealed = 1; // King is revealed
 
-    table.move_pile(0, 1, 1); // Move King to empty pile
+    table.move_pile(0, 1, 1).unwrap(); // Move King to empty pile
 
     assert!(table.piles[0].cards.is_empty()); // It got moved
-    assert_eq!(table.piles[1].cards.get(0), Some(&Card::new_fr(Suit::Coppe, 13))); // It arrived
+    assert_eq!(table.piles[1].cards.first(), Some(&Card::new_fr(Suit::Coppe, 13))); // It arrived
     
 }
+
+#[test]
+fn legal_moves_are_legal() {
+    use rand::seq::SliceRandom;
+    for seed in 0..20 {
+        let mut rng = seeded_rng(seed);
+        let mut table = Table::from_seed(seed);
+        play_out(&mut table, 500, |_, moves| moves.choose(&mut rng).copied()).unwrap();
+    }
+
+    let mut table = Table::from_seed(0);
+    assert!(table.apply_move(&ParsedMove::Quit).is_err());
+    assert!(table.apply_move(&ParsedMove::MoveFromStackToAce(4)).is_err());
+    assert!(matches!(table.make_move("undo"), Err(MoveMakingError::UndoNotImplemented)));
+    assert_eq!(table.moves, 0); // Nothing was done, nothing is counted
+}
+
+#[test]
+fn solver_finds_a_win() {
+    // Only the kings left, two of them face down
+    let mut table = Table::from_seed(0);
+    for (a, suit) in [Suit::Coppe, Suit::Denari, Suit::Bastoni, Suit::Spade].into_iter().enumerate() {
+        table.aces[a].cards = (1..=12).map(|n| Card::new_fr(suit, n)).collect();
+    }
+    table.piles = std::array::from_fn(|_i| GamePile::default());
+    table.piles[0] = GamePile { cards: vec![Card::new_fr(Suit::Spade, 13), Card::new_fr(Suit::Coppe, 13)], revealed: 1 };
+    table.piles[1] = GamePile { cards: vec![Card::new_fr(Suit::Denari, 13)], revealed: 1 };
+    table.stack = Deck(vec![Card::new_fr(Suit::Bastoni, 13)].into());
+    table.passed_stack = Deck::new();
+    assert!(!table.has_won());
+
+    let moves = solve_game(&table).unwrap();
+    for mov in &moves { table.apply(*mov).unwrap() }
+    assert!(table.has_won());
+    assert_eq!(solve_game(&table), Some(vec![]));
+}
@@ -1,17 +1,131 @@
 use crate::*;
-use std::collections::HashMap;
+use std::collections::HashSet;
 
+/// How many different tables `solve_game` looks at before giving up
+pub const SEARCH_LIMIT: usize = 200_000;
 
-pub fn solve_game(table: &Table) -> Vec<ParsedMove> {
+/// The moves that win from `table` (see `Table::has_won`), found by trying every legal move in
+/// turn: first those that turn a card over, then those to the aces, and moves back from the aces
+/// last. `None` if there's no win within `SEARCH_LIMIT` tables
+pub fn solve_game(table: &Table) -> Option<Vec<ParsedMove>> {
+    use ParsedMove as PM;
+    if table.has_won() { return Some(vec![]); }
+    let mut seen = HashSet::from([state(table)]);
+    let mut steps: Vec<(Option<usize>, ParsedMove)> = vec![]; // Index of the step before, and the move
+    let mut todo: Vec<(Table, Option<usize>)> = vec![(table.clone(), None)];
 
+    while let Some((table, step)) = todo.pop() {
+        if seen.len() > SEARCH_LIMIT { break }
 
-    todo!()
+        let mut next_tables = vec![];
+        for mov in get_legal_moves(&table) {
+            if !useful(&table, mov) { continue }
+            let mut next = table.clone();
+            if next.apply(mov).is_err() || !seen.insert(state(&next)) { continue }
+            steps.push((step, mov));
+            if next.has_won() { return Some(path(&steps, steps.len() - 1)); }
 
+            let priority = match mov {
+                _ if hidden(&next) < hidden(&table)                    => 3,
+                PM::MoveFromPileToAce { .. } | PM::MoveFromStackToAce(_) => 2,
+                PM::MoveFromAceToPile { .. }                           => 0,
+                _                                                      => 1,
+            };
+            next_tables.push((priority, next, steps.len() - 1));
+        }
+        // Last in is tried first
+        next_tables.sort_by_key(|(priority, ..)| *priority);
+        todo.extend(next_tables.into_iter().map(|(_, next, step)| (next, Some(step))));
+    }
+    None
+}
+
+/// The moves up to and including `steps[step]`
+fn path(steps: &[(Option<usize>, ParsedMove)], step: usize) -> Vec<ParsedMove> {
+    let mut moves = vec![];
+    let mut step = Some(step);
+    while let Some(i) = step {
+        moves.push(steps[i].1);
+        step = steps[i].0;
+    }
+    moves.reverse();
+    moves
+}
 
+/// Moving cards between piles only helps if it turns a card over, empties a pile or frees a card
+/// for the aces. Anything else just goes back and forth
+fn useful(table: &Table, mov: ParsedMove) -> bool {
+    let ParsedMove::MoveFromPileToPile { from, to, amount } = mov else { return true };
+    let (from, to) = (&table.piles[from as usize], &table.piles[to as usize]);
+    let left = from.cards.len() - amount as usize;
+    match left.checked_sub(1).map(|i| &from.cards[i]) {
+        None => !to.cards.is_empty(),
+        Some(_) if amount >= from.revealed => true,
+        Some(freed) => table.aces.iter().any(|a| a.accepts(freed)),
+    }
 }
 
+/// How many cards are face down in the piles
+fn hidden(table: &Table) -> usize {
+    table.piles.iter().map(|p| p.cards.len().saturating_sub(p.revealed as usize)).sum()
+}
+
+/// Piles (cards and how many are revealed), stack, passed stack and how full each ace pile is
+type State = (Vec<(Vec<Card>, u8)>, Vec<Card>, Vec<Card>, [usize; 4]);
+
+/// Everything about `table` that changes what can be played from it
+fn state(table: &Table) -> State {
+    (table.piles.iter().map(|p| (p.cards.clone(), p.revealed)).collect(),
+     table.stack.0.iter().copied().collect(),
+     table.passed_stack.0.iter().copied().collect(),
+     std::array::from_fn(|a| table.aces[a].cards.len()))
+}
+
+
+/// Every move that `Table::apply` would accept right now. `Cycle` is left out, it's just a
+/// bunch of `RevealNextOfStack`s
+pub fn get_legal_moves(table: &Table) -> Vec<ParsedMove> {
+    use ParsedMove as PM;
+    let mut moves = Vec::new();
+    if table.has_won() { return moves; }
+
+    if !table.stack.is_empty() || !table.passed_stack.is_empty() {
+        moves.push(PM::RevealNextOfStack);
+    }
+
+    if let Some(card) = table.stack.top() {
+        for (p, pile) in table.piles.iter().enumerate() {
+            if legality_check(card, pile.get_tail_of_revealed()) { moves.push(PM::MoveFromStackToPile(p as u8)) }
+        }
+        for (a, ace) in table.aces.iter().enumerate() {
+            if ace.accepts(card) { moves.push(PM::MoveFromStackToAce(a as u8)) }
+        }
+    }
+
+    for (from, pile) in table.piles.iter().enumerate() {
+        for (to, other) in table.piles.iter().enumerate() {
+            if from == to { continue }
+            for amount in 1..=pile.revealed.min(pile.cards.len() as u8) {
+                let base = pile.get_nth_revealed(amount as usize - 1).expect("amount is within the revealed cards");
+                if legality_check(base, other.get_tail_of_revealed()) {
+                    moves.push(PM::MoveFromPileToPile { from: from as u8, to: to as u8, amount });
+                }
+            }
+        }
+
+        if let Some(card) = pile.get_tail_of_revealed() {
+            for (a, ace) in table.aces.iter().enumerate() {
+                if ace.accepts(card) { moves.push(PM::MoveFromPileToAce { pile: from as u8, ace: a as u8 }) }
+            }
+        }
+    }
 
-fn get_legal_moves(table: &Table) -> Vec<ParsedMove> {
-    todo!()
+    for (a, ace) in table.aces.iter().enumerate() {
+        let Some(card) = ace.top() else { continue };
+        for (p, pile) in table.piles.iter().enumerate() {
+            if legality_check(card, pile.get_tail_of_revealed()) { moves.push(PM::MoveFromAceToPile { ace: a as u8, pile: p as u8 }) }
+        }
+    }
 
+    moves
 }
@@ -0,0 +1,138 @@
+//! What the player gets to see of the table: no face-down cards, and no order for the stack.
+
+use cards_core::*;
+
+use crate::*;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TableView {
+    /// Deepest card first, `None` for the cards that are still face down
+    pub piles: [Vec<Option<Card>>; 7],
+    /// The only card of the stack that can be seen
+    pub stack_top: Option<Card>,
+    pub stack_len: usize,
+    pub passed: usize,
+    /// Top card of each ace pile
+    pub aces: [Option<Card>; 4],
+    pub moves: usize,
+    /// Every card that's face up or has been seen going through the stack
+    pub seen: CardSet,
+}
+
+impl Table {
+    pub fn view(&self) -> TableView {
+        let piles = std::array::from_fn(|p| {
+            let GamePile { cards, revealed } = &self.piles[p];
+            let hidden = cards.len().saturating_sub(*revealed as usize);
+            cards.iter().enumerate().map(|(i, c)| (i >= hidden).then_some(*c)).collect()
+        });
+        let aces = std::array::from_fn(|a| self.aces[a].top().copied());
+
+        let mut seen: CardSet = self.passed_stack.iter().chain(self.stack.top()).collect();
+        for pile in &self.aces { seen.extend(pile.cards.iter().copied()) }
+
+        let mut view = TableView {
+            piles,
+            stack_top: self.stack.top().copied(),
+            stack_len: self.stack.len(),
+            passed: self.passed_stack.len(),
+            aces,
+            moves: self.moves,
+            seen,
+        };
+        let face_up: Vec<Card> = view.piles.iter().flatten().flatten().copied().collect();
+        view.seen.extend(face_up);
+        view
+    }
+}
+
+impl TableView {
+    /// Cards that haven't been seen yet: face down on the table or deep in the stack
+    pub fn unknown(&self) -> CardSet {
+        CardSet::full(DeckKind::French) - self.seen
+    }
+
+    pub fn render(&self, style: CardStyle) -> String {
+        let print_card = |c: &Card| style.render(c, DeckKind::French);
+        let bold = |t: &str| style.ansi("1", t);
+
+        let mut s: String = String::new();
+        s.push_str(&format!("Number of moves made is: {}\n\n", bold(&format!("'{}'", self.moves))));
+        s.push_str(&format!("{} Top is {} ---- ({} cards upside down, {} passed)\n\n",
+            bold("Stack:"),
+            self.stack_top.as_ref().map(print_card).unwrap_or("--".to_string()),
+            self.stack_len,
+            self.passed,
+            ));
+
+        let print_ace = |i: usize| self.aces[i]
+                                       .as_ref()
+                                       .map(print_card)
+                                       .unwrap_or(UNKNOWN_CARD.to_string());
+
+        s.push_str(&format!("          \t{}\t{}\t{}\t{}\n", 
+                            "[0]",
+                            "[1]",
+                            "[2]",
+                            "[3]"));
+
+        s.push_str(&format!("{}\t{}\t{}\t{}\t{}\n\n",
+                            bold("Ace piles:"),
+                            print_ace(0),
+                            print_ace(1),
+                            print_ace(2),
+                            print_ace(3)));
+
+        s.push_str(&bold("Main area:"));
+        s.push('\n');
+
+        let max_index: usize = self.piles.iter()
+            .map(|p| p.len())       // All lens
+            .max().unwrap()         // Max len
+            .max(1) - 1;            // Clamp to 1, turn into index
+
+        s.push_str(&(0..7).map(|i| format!("[{i}]")).collect::<Vec<String>>().join("\t"));
+        s.push('\n');
+        s.push_str(&(0..7).map(|_| "===".to_string()).collect::<Vec<String>>().join("\t"));
+        s.push('\n');
+
+        for depth in 0..=max_index {
+            for pile in &self.piles {
+                match pile.get(depth) {
+                    None             => {}, // Nothing
+                    Some(Some(card)) => s.push_str(&print_card(card)),
+                    Some(None)       => s.push_str(style.back()),
+                }
+                s.push('\t');
+            }
+            s.push('\n');
+        }
+        s.push_str("\n\n");
+        s
+    }
+}
+
+impl HiddenInformation for Table {
+    type View = TableView;
+
+    fn view(&self, _player: ()) -> TableView {
+        Table::view(self)
+    }
+}
+
+
+// ============ TESTS ================
+#[test]
+fn face_down_cards_stay_hidden() {
+    let table = Table::from_seed(3);
+    let view = table.view();
+
+    for (p, pile) in view.piles.iter().enumerate() {
+        assert_eq!(pile.len(), p + 1);
+        assert_eq!(pile.iter().filter(|c| c.is_none()).count(), p);
+        assert_eq!(pile.last().copied().flatten(), table.piles[p].cards.last().copied());
+    }
+    assert_eq!(view.seen.len(), 7 + 1); // The bottom of each pile and the top of the stack
+    assert_eq!(view.unknown().len(), 52 - 8);
+    assert!(!view.unknown().contains(&table.stack.top().copied().unwrap()));
+}